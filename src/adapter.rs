@@ -0,0 +1,476 @@
+use crate::{util, AlphaSource, Cvd, SpreadMethod};
+use colorgrad::{Color, Gradient};
+
+// Sub-range start..end of a gradient stretched over its whole domain
+#[derive(Clone)]
+pub struct Slice {
+    grad: Box<dyn Gradient>,
+    start: f32,
+    end: f32,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Slice {
+    pub fn new(grad: Box<dyn Gradient>, start: f32, end: f32) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            start,
+            end,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Slice {
+    fn at(&self, t: f32) -> Color {
+        self.grad
+            .at(util::remap(t, self.dmin, self.dmax, self.start, self.end))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient with positions mapped t -> dmin + dmax - t
+#[derive(Clone)]
+pub struct Reversed {
+    grad: Box<dyn Gradient>,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Reversed {
+    pub fn new(grad: Box<dyn Gradient>) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self { grad, dmin, dmax }
+    }
+}
+
+impl Gradient for Reversed {
+    fn at(&self, t: f32) -> Color {
+        self.grad.at(self.dmin + self.dmax - t)
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient with its domain linearly mapped to [dmin, dmax]
+#[derive(Clone)]
+pub struct Remap {
+    grad: Box<dyn Gradient>,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Remap {
+    pub fn new(grad: Box<dyn Gradient>, dmin: f32, dmax: f32) -> Self {
+        Self { grad, dmin, dmax }
+    }
+}
+
+impl Gradient for Remap {
+    fn at(&self, t: f32) -> Color {
+        let (gmin, gmax) = self.grad.domain();
+        self.grad
+            .at(util::remap(t, self.dmin, self.dmax, gmin, gmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient cycled N times across its domain, optionally reflected
+#[derive(Clone)]
+pub struct Repeat {
+    grad: Box<dyn Gradient>,
+    n: f32,
+    mirror: bool,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Repeat {
+    pub fn new(grad: Box<dyn Gradient>, n: usize, mirror: bool) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            n: n as f32,
+            mirror,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Repeat {
+    fn at(&self, t: f32) -> Color {
+        let t = util::remap(t, self.dmin, self.dmax, 0.0, 1.0);
+        let t = util::repeat_t(t, self.n);
+        let t = if self.mirror { util::mirror_t(t) } else { t };
+        self.grad.at(util::remap(t, 0.0, 1.0, self.dmin, self.dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// SVG spreadMethod applied to positions outside the domain
+#[derive(Clone)]
+pub struct Spread {
+    grad: Box<dyn Gradient>,
+    method: SpreadMethod,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Spread {
+    pub fn new(grad: Box<dyn Gradient>, method: SpreadMethod) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            method,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Spread {
+    fn at(&self, t: f32) -> Color {
+        let t = util::remap(t, self.dmin, self.dmax, 0.0, 1.0);
+        let t = util::spread_t(t, self.method);
+        self.grad.at(util::remap(t, 0.0, 1.0, self.dmin, self.dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient scrolled by phase (fraction of the domain), wrapping around
+#[derive(Clone)]
+pub struct Shift {
+    grad: Box<dyn Gradient>,
+    phase: f32,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Shift {
+    pub fn new(grad: Box<dyn Gradient>, phase: f32) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            phase,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Shift {
+    fn at(&self, t: f32) -> Color {
+        let t = util::remap(t, self.dmin, self.dmax, 0.0, 1.0);
+        let t = (t + self.phase).rem_euclid(1.0);
+        self.grad.at(util::remap(t, 0.0, 1.0, self.dmin, self.dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient posterized into N flat bands
+#[derive(Clone)]
+pub struct Steps {
+    grad: Box<dyn Gradient>,
+    n: usize,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Steps {
+    pub fn new(grad: Box<dyn Gradient>, n: usize) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            n,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Steps {
+    fn at(&self, t: f32) -> Color {
+        let t = util::remap(t, self.dmin, self.dmax, 0.0, 1.0);
+        let t = util::step_t(t, self.n);
+        self.grad.at(util::remap(t, 0.0, 1.0, self.dmin, self.dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Gradient colors as seen with color vision deficiency
+#[derive(Clone)]
+pub struct Simulate {
+    grad: Box<dyn Gradient>,
+    kind: Cvd,
+}
+
+impl Simulate {
+    pub fn new(grad: Box<dyn Gradient>, kind: Cvd) -> Self {
+        Self { grad, kind }
+    }
+}
+
+impl Gradient for Simulate {
+    fn at(&self, t: f32) -> Color {
+        util::simulate_cvd(&self.grad.at(t), self.kind)
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.grad.domain()
+    }
+}
+
+// Gradient with the lightness of each color inverted
+#[derive(Clone)]
+pub struct InvertLightness {
+    grad: Box<dyn Gradient>,
+}
+
+impl InvertLightness {
+    pub fn new(grad: Box<dyn Gradient>) -> Self {
+        Self { grad }
+    }
+}
+
+impl Gradient for InvertLightness {
+    fn at(&self, t: f32) -> Color {
+        util::invert_lightness(&self.grad.at(t))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.grad.domain()
+    }
+}
+
+// Gradient with alpha scaled by a constant factor
+#[derive(Clone)]
+pub struct Opacity {
+    grad: Box<dyn Gradient>,
+    opacity: f32,
+}
+
+impl Opacity {
+    pub fn new(grad: Box<dyn Gradient>, opacity: f32) -> Self {
+        Self {
+            grad,
+            opacity: if opacity.is_nan() {
+                1.0
+            } else {
+                opacity.clamp(0.0, 1.0)
+            },
+        }
+    }
+}
+
+impl Gradient for Opacity {
+    fn at(&self, t: f32) -> Color {
+        let mut col = self.grad.at(t);
+        col.a *= self.opacity;
+        col
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.grad.domain()
+    }
+}
+
+// Gradient with alpha derived from each color
+#[derive(Clone)]
+pub struct AlphaFrom {
+    grad: Box<dyn Gradient>,
+    source: AlphaSource,
+}
+
+impl AlphaFrom {
+    pub fn new(grad: Box<dyn Gradient>, source: AlphaSource) -> Self {
+        Self { grad, source }
+    }
+}
+
+impl Gradient for AlphaFrom {
+    fn at(&self, t: f32) -> Color {
+        let mut col = self.grad.at(t).clamp();
+        col.a = match self.source {
+            AlphaSource::Luminance => util::relative_luminance(&col),
+            AlphaSource::InvLuminance => 1.0 - util::relative_luminance(&col),
+            AlphaSource::Value => col.r.max(col.g).max(col.b),
+        };
+        col
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.grad.domain()
+    }
+}
+
+// Gradient with a cubic-bezier easing applied across its domain
+#[derive(Clone)]
+pub struct Ease {
+    grad: Box<dyn Gradient>,
+    params: [f32; 4],
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Ease {
+    pub fn new(grad: Box<dyn Gradient>, params: [f32; 4]) -> Self {
+        let (dmin, dmax) = grad.domain();
+        Self {
+            grad,
+            params,
+            dmin,
+            dmax,
+        }
+    }
+}
+
+impl Gradient for Ease {
+    fn at(&self, t: f32) -> Color {
+        let t = util::remap(t, self.dmin, self.dmax, 0.0, 1.0);
+        let t = util::cubic_bezier(self.params, t);
+        self.grad.at(util::remap(t, 0.0, 1.0, self.dmin, self.dmax))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// Two gradients mixed in Oklab, ratio 0 is pure A, 1 is pure B
+#[derive(Clone)]
+pub struct Mix {
+    a: Box<dyn Gradient>,
+    b: Box<dyn Gradient>,
+    ratio: f32,
+}
+
+impl Mix {
+    pub fn new(a: Box<dyn Gradient>, b: Box<dyn Gradient>, ratio: f32) -> Self {
+        Self {
+            a,
+            b,
+            ratio: ratio.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Gradient for Mix {
+    fn at(&self, t: f32) -> Color {
+        let (amin, amax) = self.a.domain();
+        let (bmin, bmax) = self.b.domain();
+        let col = self.b.at(util::remap(t, amin, amax, bmin, bmax));
+        self.a.at(t).interpolate_oklab(&col, self.ratio)
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        self.a.domain()
+    }
+}
+
+#[test]
+fn opacity() {
+    let grad = || {
+        colorgrad::GradientBuilder::new()
+            .html_colors(&["#f00", "rgb(0 0 255 / 50%)"])
+            .build::<colorgrad::LinearGradient>()
+            .unwrap()
+    };
+    let half = Opacity::new(Box::new(grad()), 0.5);
+    assert_eq!(half.at(0.0).a, 0.5);
+    assert_eq!(half.at(1.0).a, 0.25);
+    assert_eq!(Opacity::new(Box::new(grad()), 2.0).at(0.0).a, 1.0);
+}
+
+#[test]
+fn slice() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#000", "#fff"])
+        .mode(colorgrad::BlendMode::Rgb)
+        .build::<colorgrad::LinearGradient>()
+        .unwrap();
+    let mid = Slice::new(Box::new(grad), 0.25, 0.75);
+    assert_eq!(mid.domain(), (0.0, 1.0));
+    assert_eq!(mid.at(0.0).to_rgba8(), [64, 64, 64, 255]);
+    assert_eq!(mid.at(0.5).to_rgba8(), [128, 128, 128, 255]);
+    assert_eq!(mid.at(1.0).to_rgba8(), [191, 191, 191, 255]);
+}
+
+#[test]
+fn repeat() {
+    // Each seam starts the next cycle, only the very end is the end color
+    assert_eq!(util::repeat_t(0.0, 2.0), 0.0);
+    assert_eq!(util::repeat_t(0.25, 2.0), 0.5);
+    assert_eq!(util::repeat_t(0.5, 2.0), 0.0);
+    assert_eq!(util::repeat_t(0.75, 2.0), 0.5);
+    assert_eq!(util::repeat_t(1.0, 2.0), 1.0);
+    assert_eq!(util::repeat_t(2.0 / 3.0, 3.0), 0.0);
+    assert_eq!(util::repeat_t(-0.5, 2.0), 0.0);
+    assert_eq!(util::repeat_t(1.5, 2.0), 1.0);
+
+    let grad = || {
+        colorgrad::GradientBuilder::new()
+            .html_colors(&["#000", "#fff"])
+            .mode(colorgrad::BlendMode::Rgb)
+            .domain(&[10.0, 20.0])
+            .build::<colorgrad::LinearGradient>()
+            .unwrap()
+    };
+    let rep = Repeat::new(Box::new(grad()), 2, false);
+    assert_eq!(rep.domain(), (10.0, 20.0));
+    assert_eq!(rep.at(12.5).to_rgba8(), rep.at(17.5).to_rgba8());
+    assert_eq!(rep.at(15.0).to_rgba8(), [0, 0, 0, 255]);
+    assert_eq!(rep.at(20.0).to_rgba8(), [255, 255, 255, 255]);
+
+    let mirrored = Repeat::new(Box::new(grad()), 2, true);
+    assert_eq!(mirrored.at(12.5).to_rgba8(), [255, 255, 255, 255]);
+    assert_eq!(mirrored.at(15.0).to_rgba8(), [0, 0, 0, 255]);
+}
+
+#[test]
+fn alpha_from() {
+    let grad = || {
+        colorgrad::GradientBuilder::new()
+            .html_colors(&["#fff", "#000"])
+            .build::<colorgrad::LinearGradient>()
+            .unwrap()
+    };
+    let lum = AlphaFrom::new(Box::new(grad()), AlphaSource::Luminance);
+    assert_eq!(lum.at(0.0).a, 1.0);
+    assert_eq!(lum.at(1.0).a, 0.0);
+    assert_eq!(lum.at(0.0).to_rgba8(), [255, 255, 255, 255]);
+
+    let inv = AlphaFrom::new(Box::new(grad()), AlphaSource::InvLuminance);
+    assert_eq!(inv.at(0.0).a, 0.0);
+    assert_eq!(inv.at(1.0).a, 1.0);
+
+    // Opacity applies on top
+    let half = Opacity::new(
+        Box::new(AlphaFrom::new(Box::new(grad()), AlphaSource::Value)),
+        0.5,
+    );
+    assert_eq!(half.at(0.0).a, 0.5);
+}
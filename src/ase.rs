@@ -0,0 +1,167 @@
+use crate::palette::{Palette, PaletteError};
+use colorgrad::Color;
+
+const GROUP_START: u16 = 0xc001;
+const GROUP_END: u16 = 0xc002;
+const COLOR_ENTRY: u16 = 0x0001;
+
+// Big-endian reader over the file bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], PaletteError> {
+        if self.data.len() - self.pos < n {
+            return Err(PaletteError::new(0, "unexpected end of file"));
+        }
+        let s = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(s)
+    }
+
+    fn u16(&mut self) -> Result<u16, PaletteError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, PaletteError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, PaletteError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
+
+// Color entry: UTF-16 name, 4 char color model, model values and a color type
+fn parse_color(block: &[u8]) -> Result<Color, PaletteError> {
+    let mut r = Reader {
+        data: block,
+        pos: 0,
+    };
+
+    let name_len = r.u16()? as usize;
+    r.take(name_len * 2)?;
+
+    let model = r.take(4)?;
+    let color = match model {
+        b"RGB " => {
+            let (red, green, blue) = (r.f32()?, r.f32()?, r.f32()?);
+            Color::new(red, green, blue, 1.0)
+        }
+        b"CMYK" => {
+            let (c, m, y, k) = (r.f32()?, r.f32()?, r.f32()?, r.f32()?);
+            Color::new(
+                (1.0 - c) * (1.0 - k),
+                (1.0 - m) * (1.0 - k),
+                (1.0 - y) * (1.0 - k),
+                1.0,
+            )
+        }
+        b"Gray" => {
+            let v = r.f32()?;
+            Color::new(v, v, v, 1.0)
+        }
+        // L is stored in 0..1
+        b"LAB " => {
+            let (l, a, b) = (r.f32()?, r.f32()?, r.f32()?);
+            Color::from_laba(l * 100.0, a, b, 1.0)
+        }
+        _ => {
+            return Err(PaletteError::new(
+                0,
+                &format!("unknown color model '{}'", String::from_utf8_lossy(model)),
+            ))
+        }
+    };
+
+    Ok(color.clamp())
+}
+
+// Adobe Swatch Exchange (ase)
+pub fn parse_ase(data: &[u8]) -> Result<Palette, PaletteError> {
+    let mut r = Reader { data, pos: 0 };
+
+    if r.take(4).ok() != Some(b"ASEF".as_slice()) {
+        return Err(PaletteError::new(0, "not an ASE file"));
+    }
+
+    let major = r.u16()?;
+    let _minor = r.u16()?;
+    if major != 1 {
+        return Err(PaletteError::new(
+            0,
+            &format!("unsupported version {major}"),
+        ));
+    }
+
+    let blocks = r.u32()?;
+    let mut colors = Vec::new();
+
+    for _ in 0..blocks {
+        let kind = r.u16()?;
+        let len = r.u32()? as usize;
+        let block = r.take(len)?;
+
+        match kind {
+            COLOR_ENTRY => colors.push(parse_color(block)?),
+            GROUP_START | GROUP_END => {}
+            _ => {
+                return Err(PaletteError::new(
+                    0,
+                    &format!("unknown block type {kind:#06x}"),
+                ))
+            }
+        }
+    }
+
+    if colors.is_empty() {
+        return Err(PaletteError::new(0, "no colors"));
+    }
+
+    Ok(Palette {
+        name: None,
+        colors,
+        pos: None,
+    })
+}
+
+#[test]
+fn ase_swatches() {
+    fn block(kind: u16, body: &[u8]) -> Vec<u8> {
+        let mut b = kind.to_be_bytes().to_vec();
+        b.extend((body.len() as u32).to_be_bytes());
+        b.extend(body);
+        b
+    }
+
+    fn entry(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+        let name: Vec<u16> = name.encode_utf16().chain([0]).collect();
+        let mut b = (name.len() as u16).to_be_bytes().to_vec();
+        name.iter().for_each(|c| b.extend(c.to_be_bytes()));
+        b.extend(model);
+        values.iter().for_each(|v| b.extend(v.to_be_bytes()));
+        b.extend(2u16.to_be_bytes());
+        block(COLOR_ENTRY, &b)
+    }
+
+    let mut data = b"ASEF".to_vec();
+    data.extend([0, 1, 0, 0]);
+    data.extend(6u32.to_be_bytes());
+    data.extend(block(GROUP_START, &[0, 2, 0, b'G', 0, 0]));
+    data.extend(entry("red", b"RGB ", &[1.0, 0.0, 0.0]));
+    data.extend(entry("cyan", b"CMYK", &[1.0, 0.0, 0.0, 0.0]));
+    data.extend(entry("gray", b"Gray", &[0.5]));
+    data.extend(entry("white", b"LAB ", &[1.0, 0.0, 0.0]));
+    data.extend(block(GROUP_END, &[]));
+
+    let pal = parse_ase(&data).unwrap();
+    let hex: Vec<_> = pal.colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#ff0000", "#00ffff", "#808080", "#ffffff"]);
+
+    assert!(parse_ase(&data[..data.len() - 3]).is_err());
+    assert!(parse_ase(b"GIMP").is_err());
+}
@@ -1,30 +1,129 @@
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
 use clap::{Parser, ValueEnum};
 use colorgrad::Color;
 use std::path::PathBuf;
 
-#[derive(Clone, ValueEnum)]
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
 pub enum BlendMode {
     Rgb,
     LinearRgb,
     Oklab,
     Lab,
+    Oklch,
+    Hsluv,
+    Hsl,
+    Hsv,
 }
 
-#[derive(Clone, ValueEnum)]
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
 pub enum Interpolation {
     Linear,
     Basis,
     CatmullRom,
+    /// Monotone cubic, no overshoot past the input colors
+    Monotone,
+    /// Linear with cubic-bezier easing, see --bezier-params
+    Bezier,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum BarChar {
+    Full,
+    Half,
+    Ascii,
+    Shade,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum Cvd {
+    Protan,
+    Deutan,
+    Tritan,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum ColorDepth {
+    Auto,
+    #[value(name = "24bit")]
+    TrueColor,
+    #[value(name = "8bit")]
+    Ansi256,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum Dither {
+    None,
+    Ordered,
+    ErrorDiffusion,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum PlotSpace {
+    Rgb,
+    Hsl,
+    Oklab,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum AlphaSource {
+    /// Relative luminance, black is transparent
+    Luminance,
+    /// Inverted relative luminance, white is transparent
+    InvLuminance,
+    /// HSV value (brightest channel)
+    Value,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum Edges {
+    /// First and last color are the gradient ends
+    Inclusive,
+    /// Colors at the centers of N equal cells, --take 1 is the midpoint
+    Centered,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum SortBy {
+    None,
+    /// Relative luminance, dark to light
+    Luminance,
+    /// HSL hue
+    Hue,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum HexAlpha {
+    /// Only when alpha < 1
+    Auto,
+    Always,
+    Never,
 }
 
 #[derive(Copy, Clone, PartialEq, ValueEnum)]
 pub enum OutputColor {
     Hex,
+    /// rgb() with percentage channels, e.g. rgb(100.00%,0.00%,0.00%)
     Rgb,
+    /// rgb() with integer 0-255 channels, e.g. rgb(255,0,0)
     Rgb255,
     Hsl,
     Hsv,
     Hwb,
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+    Name,
+    /// 32-bit integer with alpha, e.g. 0xff0000ff, channel order from --packed-order
+    Packed,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum PackedOrder {
+    /// 0xRRGGBBAA
+    Rgba,
+    /// 0xAARRGGBB
+    Argb,
 }
 
 pub const PRESET_NAMES: [&str; 38] = [
@@ -68,6 +167,207 @@ pub const PRESET_NAMES: [&str; 38] = [
     "yl-or-rd",
 ];
 
+// --sample position, keywords are resolved against the gradient domain
+#[derive(Copy, Clone)]
+pub enum SamplePos {
+    Start,
+    Mid,
+    End,
+    Value(f32),
+}
+
+impl SamplePos {
+    pub fn resolve(self, dmin: f32, dmax: f32) -> f32 {
+        match self {
+            SamplePos::Start => dmin,
+            SamplePos::Mid => (dmin + dmax) / 2.0,
+            SamplePos::End => dmax,
+            SamplePos::Value(t) => t,
+        }
+    }
+}
+
+impl std::str::FromStr for SamplePos {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_ref() {
+            "start" | "min" => Ok(SamplePos::Start),
+            "mid" => Ok(SamplePos::Mid),
+            "end" | "max" => Ok(SamplePos::End),
+            v => v.parse::<f32>().map(SamplePos::Value).map_err(|_| {
+                format!("invalid position '{s}', expected a number, start, mid or end")
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Background {
+    /// Query the terminal background color
+    Auto,
+    Color(Color),
+}
+
+impl std::str::FromStr for Background {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            return Ok(Background::Auto);
+        }
+        s.parse::<Color>()
+            .map(Background::Color)
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Swatch grid size, ROWSxCOLS
+#[derive(Copy, Clone)]
+pub struct Grid {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl std::str::FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid grid '{s}', expected ROWSxCOLS (e.g. 4x8)");
+        let (r, c) = s.trim().split_once(['x', 'X']).ok_or_else(err)?;
+        let rows = r.trim().parse::<usize>().map_err(|_| err())?;
+        let cols = c.trim().parse::<usize>().map_err(|_| err())?;
+        if rows == 0 || cols == 0 {
+            return Err(err());
+        }
+        Ok(Grid { rows, cols })
+    }
+}
+
+// Cubic-bezier easing control points x1,y1,x2,y2 as in CSS cubic-bezier()
+#[derive(Copy, Clone)]
+pub struct BezierParams(pub [f32; 4]);
+
+impl Default for BezierParams {
+    // CSS ease-in-out
+    fn default() -> Self {
+        BezierParams([0.42, 0.0, 0.58, 1.0])
+    }
+}
+
+impl std::str::FromStr for BezierParams {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err =
+            || format!("invalid bezier params '{s}', expected X1,Y1,X2,Y2 (e.g. 0.42,0,0.58,1)");
+        let v = s
+            .split(',')
+            .map(|n| n.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err())?;
+        if v.len() != 4 || v.iter().any(|n| !n.is_finite()) {
+            return Err(err());
+        }
+        if !(0.0..=1.0).contains(&v[0]) || !(0.0..=1.0).contains(&v[2]) {
+            return Err(format!(
+                "invalid bezier params '{s}', X1 and X2 must be in [0, 1]"
+            ));
+        }
+        Ok(BezierParams([v[0], v[1], v[2], v[3]]))
+    }
+}
+
+// Gradient direction for CSS / SVG export, CSS convention: 0deg is up, 90deg is to the right
+#[derive(Clone)]
+pub struct Angle {
+    pub css: String,
+    pub degrees: f32,
+}
+
+impl std::str::FromStr for Angle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!("invalid angle '{s}', expected degrees (e.g. 45deg) or to [left|right] [top|bottom]")
+        };
+        let v = s.trim().to_lowercase();
+
+        if let Some(rest) = v.strip_prefix("to ") {
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let (mut x, mut y) = (None, None);
+            for w in &words {
+                match *w {
+                    "left" if x.is_none() => x = Some(-1.0),
+                    "right" if x.is_none() => x = Some(1.0),
+                    "top" if y.is_none() => y = Some(1.0),
+                    "bottom" if y.is_none() => y = Some(-1.0),
+                    _ => return Err(err()),
+                }
+            }
+            if words.is_empty() {
+                return Err(err());
+            }
+            let (x, y): (f32, f32) = (x.unwrap_or(0.0), y.unwrap_or(0.0));
+            return Ok(Angle {
+                css: format!("to {}", words.join(" ")),
+                degrees: x.atan2(y).to_degrees().rem_euclid(360.0),
+            });
+        }
+
+        let degrees = if let Some(n) = v.strip_suffix("deg") {
+            n.trim().parse::<f32>()
+        } else if let Some(n) = v.strip_suffix("turn") {
+            n.trim().parse::<f32>().map(|t| t * 360.0)
+        } else {
+            v.parse::<f32>()
+        }
+        .map_err(|_| err())?;
+
+        if !degrees.is_finite() {
+            return Err(err());
+        }
+
+        Ok(Angle {
+            css: format!("{degrees}deg"),
+            degrees,
+        })
+    }
+}
+
+// --gamma, a finite number above 0
+fn parse_gamma(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(g) if g.is_finite() && g > 0.0 => Ok(g),
+        _ => Err(format!(
+            "invalid gamma '{s}', expected a number greater than 0"
+        )),
+    }
+}
+
+// Accepts any name, unknown names are reported (with suggestion) by the app.
+// Possible values are still listed for shell completions.
+#[derive(Clone)]
+pub struct PresetNameParser;
+
+impl TypedValueParser for PresetNameParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(PRESET_NAMES.iter().map(PossibleValue::new)))
+    }
+}
+
 const EXTRA_HELP: &str =
     "\x1B[1mCOLOR\x1B[0m can be specified using CSS color format <https://www.w3.org/TR/css-color-4/>.";
 
@@ -95,27 +395,39 @@ const EXTRA_LONG_HELP: &str = "\x1B[1;4mUsage Examples:\x1B[0m
 #[derive(Clone, Default, Parser)]
 #[command(name = "gradient", author, version, about, after_help = EXTRA_HELP, after_long_help = EXTRA_LONG_HELP)]
 pub struct Opt {
-    /// Lists all available preset gradient names
-    #[arg(short = 'l', long, help_heading = Some("PRESET GRADIENT"))]
-    pub list_presets: bool,
+    /// Lists all available preset gradient names, optionally only names containing SEARCH
+    #[arg(short = 'l', long, num_args = 0..=1, value_name = "SEARCH", help_heading = Some("PRESET GRADIENT"))]
+    pub list_presets: Option<Option<String>>,
 
-    /// Use the preset gradient
-    #[arg(short = 'p', long, value_parser = PRESET_NAMES, hide_possible_values = true, value_name = "NAME", help_heading = Some("PRESET GRADIENT"))]
-    pub preset: Option<String>,
+    /// With --list-presets, print the colors at start, middle and end after each name in --format
+    #[arg(long, requires = "list_presets", help_heading = Some("PRESET GRADIENT"))]
+    pub with_colors: bool,
+
+    /// Use the preset gradient(s)
+    #[arg(short = 'p', long, num_args = 1.., value_parser = PresetNameParser, hide_possible_values = true, value_name = "NAME", help_heading = Some("PRESET GRADIENT"))]
+    pub preset: Option<Vec<String>>,
+
+    /// Mix two preset gradients, RATIO 0 is pure PRESET-A, 1 is pure PRESET-B
+    #[arg(long, num_args = 3, allow_negative_numbers = true, value_names = ["PRESET-A", "PRESET-B", "RATIO"], conflicts_with = "preset", help_heading = Some("PRESET GRADIENT"))]
+    pub mix: Option<Vec<String>>,
+
+    /// Blackbody radiation gradient from MIN_K to MAX_K kelvin (1667 to 25000)
+    #[arg(long, alias = "temperature", num_args = 2, value_names = ["MIN_K", "MAX_K"], conflicts_with_all = ["preset", "mix", "custom", "css", "file"], help_heading = Some("PRESET GRADIENT"))]
+    pub blackbody: Option<Vec<f32>>,
 
     /// Create custom gradient with the specified colors
-    #[arg(short = 'c', long, num_args = 1.., value_delimiter = ';', value_name = "COLOR", conflicts_with = "preset", help_heading = Some("CUSTOM GRADIENT"))]
+    #[arg(short = 'c', long, num_args = 1.., value_delimiter = ';', value_name = "COLOR", conflicts_with_all = ["preset", "mix"], help_heading = Some("CUSTOM GRADIENT"))]
     pub custom: Option<Vec<Color>>,
 
     /// Custom gradient color position
     #[arg(short = 'P', long, allow_negative_numbers = true, num_args = 2.., value_name = "FLOAT", help_heading = Some("CUSTOM GRADIENT"))]
     pub position: Option<Vec<f32>>,
 
-    /// Custom gradient using CSS gradient format
-    #[arg(long, value_name = "CSS-GRADIENT", help_heading = Some("CUSTOM GRADIENT"))]
+    /// Custom gradient using CSS gradient format, a linear/radial/conic-gradient() wrapper is stripped
+    #[arg(long, value_name = "CSS-GRADIENT", conflicts_with_all = ["preset", "mix"], help_heading = Some("CUSTOM GRADIENT"))]
     pub css: Option<String>,
 
-    /// Custom gradient blending mode [default: oklab]
+    /// Custom gradient blending mode [default: oklab] (oklch, hsluv, hsl, hsv: linear or bezier interpolation only)
     #[arg(short = 'm', long, value_enum, value_name = "COLOR-SPACE", help_heading = Some("CUSTOM GRADIENT"))]
     pub blend_mode: Option<BlendMode>,
 
@@ -123,6 +435,10 @@ pub struct Opt {
     #[arg(short = 'i', long, value_enum, value_name = "MODE", help_heading = Some("CUSTOM GRADIENT"))]
     pub interpolation: Option<Interpolation>,
 
+    /// Easing control points for --interpolation bezier [default: 0.42,0,0.58,1]
+    #[arg(long, value_name = "X1,Y1,X2,Y2", allow_hyphen_values = true, help_heading = Some("CUSTOM GRADIENT"))]
+    pub bezier_params: Option<BezierParams>,
+
     /// GGR background color [default: white]
     #[arg(long, value_name = "COLOR", help_heading = Some("GRADIENT FILE"))]
     pub ggr_bg: Option<Color>,
@@ -135,7 +451,15 @@ pub struct Opt {
     #[arg(long, value_name = "ID", help_heading = Some("GRADIENT FILE"))]
     pub svg_id: Option<String>,
 
-    /// Read gradient from SVG or GIMP gradient (ggr) file(s)
+    /// Treat SVG stops with decreasing offsets as an error instead of clamping them
+    #[arg(long, help_heading = Some("GRADIENT FILE"))]
+    pub strict_svg: bool,
+
+    /// Color used for SVG stop-color currentColor and inherit [default: black]
+    #[arg(long, value_name = "COLOR", help_heading = Some("GRADIENT FILE"))]
+    pub svg_current_color: Option<Color>,
+
+    /// Read gradient from SVG, GIMP gradient (ggr), GIMP palette (gpl), Fractint palette (map), Adobe Color Table (act), Adobe Swatch Exchange (ase), JSON color array (json) or color list (txt, hex, csv) file(s)
     #[arg(
         short = 'f',
         long,
@@ -146,38 +470,328 @@ pub struct Opt {
     )]
     pub file: Option<Vec<PathBuf>>,
 
+    /// Re-render the --file gradient(s) whenever a file changes, until Ctrl-C (terminal only)
+    #[arg(long, requires = "file", conflicts_with = "animate", help_heading = Some("GRADIENT FILE"))]
+    pub watch: bool,
+
+    /// Don't print file name and gradient name headers
+    #[arg(short = 'q', long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print domain, blend mode, interpolation and stop details of each gradient to stderr
+    #[arg(short = 'v', long)]
+    pub verbose: bool,
+
+    /// Create gradient from the dominant colors of an image (png, jpeg, webp), sorted by luminance
+    #[arg(long, value_name = "FILE", value_parser = clap::value_parser!(PathBuf), group = "colors_source", conflicts_with_all = ["preset", "mix", "blackbody", "custom", "css", "file"], help_heading = Some("IMAGE GRADIENT"))]
+    pub from_image: Option<PathBuf>,
+
+    /// Generate a random gradient from --seed, dark to light with golden ratio spaced hues
+    #[arg(long, group = "colors_source", conflicts_with_all = ["preset", "mix", "blackbody", "custom", "css", "file"], help_heading = Some("GENERATED GRADIENT"))]
+    pub generate: bool,
+
+    /// Number of colors to extract with --from-image or to create with --generate [default: 5]
+    #[arg(long, value_name = "NUM", requires = "colors_source", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=256), help_heading = Some("IMAGE GRADIENT"))]
+    pub colors: Option<usize>,
+
     /// Gradient display width [default: terminal width]
     #[arg(short = 'W', long, value_name = "NUM")]
     pub width: Option<usize>,
 
-    /// Gradient display height [default: 2]
+    /// Gradient display width as percentage of terminal width
+    #[arg(long, value_name = "1-100", conflicts_with = "width", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=100))]
+    pub width_percent: Option<usize>,
+
+    /// Gradient display height [default: 2, vertical: 16]
     #[arg(short = 'H', long, value_name = "NUM")]
     pub height: Option<usize>,
 
-    /// Background color [default: checkerboard]
+    /// Display the gradient top to bottom
+    #[arg(long)]
+    pub vertical: bool,
+
+    /// Print tick marks and domain labels under the gradient (terminal only)
+    #[arg(long, conflicts_with = "vertical")]
+    pub ruler: bool,
+
+    /// Display multiple presets or files as stacked bars with a name column
+    #[arg(long, conflicts_with = "vertical")]
+    pub compare: bool,
+
+    /// Display the gradient as a sixel image, needs a terminal with sixel support (e.g. mlterm, WezTerm, foot)
+    #[arg(long, conflicts_with_all = ["vertical", "animate", "compare"])]
+    pub sixel: bool,
+
+    /// Display the gradient with braille dots, 2x4 per cell, dot density follows the lightness (terminal only)
+    #[arg(long, conflicts_with_all = ["vertical", "animate", "compare", "sixel"])]
+    pub braille: bool,
+
+    /// Display the gradient as an inline PNG image on Kitty or iTerm2, half-blocks on other terminals
+    #[arg(
+        long,
+        alias = "bitmap-preview",
+        conflicts_with_all = ["vertical", "animate", "compare", "sixel", "braille"]
+    )]
+    pub inline_image: bool,
+
+    /// Render the gradient bar as in a terminal even when stdout is not one, e.g. to save the escape sequences to a file
+    #[arg(long, conflicts_with_all = ["animate", "watch"])]
+    pub ansi_output: bool,
+
+    /// Scroll the gradient in the terminal until --frames are shown or Ctrl-C, best with --repeat
+    #[arg(long, conflicts_with = "vertical")]
+    pub animate: bool,
+
+    /// Number of animation frames [default: unlimited]
+    #[arg(long, value_name = "NUM", requires = "animate")]
+    pub frames: Option<usize>,
+
+    /// Animation frames per second [default: 30]
+    #[arg(long, value_name = "NUM", requires = "animate", value_parser = clap::builder::RangedU64ValueParser::<u32>::new().range(1..=240))]
+    pub fps: Option<u32>,
+
+    /// Character used to draw the gradient [default: half]
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub bar_char: Option<BarChar>,
+
+    /// Dithering used for 8-bit color output [default: none]
+    #[arg(long, value_enum, value_name = "METHOD")]
+    pub dither: Option<Dither>,
+
+    /// Remap the gradient domain to MIN..MAX, --sample positions use this range
+    #[arg(long, num_args = 2, allow_negative_numbers = true, value_names = ["MIN", "MAX"], conflicts_with = "position")]
+    pub domain: Option<Vec<f32>>,
+
+    /// Display gradient posterized into N flat bands
+    #[arg(long, value_name = "NUM", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub steps: Option<usize>,
+
+    /// Sample the gradient through a lookup table of N colors, faster for huge --take
+    #[arg(long, value_name = "NUM", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..))]
+    pub lut_size: Option<usize>,
+
+    /// Display gamma, each channel is raised to 1/GAMMA [default: 1.0]
+    #[arg(long, value_name = "FLOAT", value_parser = parse_gamma)]
+    pub gamma: Option<f32>,
+
+    /// Multiply the alpha of every color by FLOAT (0..1)
+    #[arg(long, value_name = "FLOAT")]
+    pub opacity: Option<f32>,
+
+    /// Replace the alpha of every color, applied before --opacity
+    #[arg(long, value_enum, value_name = "SOURCE")]
+    pub alpha_from: Option<AlphaSource>,
+
+    /// Multiply RGB by alpha in --take and --sample colors and in saved png / webp images
+    #[arg(long)]
+    pub premultiply: bool,
+
+    /// Background color, or auto to use the terminal background [default: checkerboard]
     #[arg(short = 'b', long, value_name = "COLOR")]
-    pub background: Option<Color>,
+    pub background: Option<Background>,
 
-    /// Checkerboard color
-    #[arg(long, number_of_values = 2, value_name = "COLOR")]
+    /// Composite the displayed gradient over a preset or CSS gradient, sampled at the same position
+    #[arg(long, alias = "mix-background", value_name = "GRADIENT")]
+    pub over_gradient: Option<String>,
+
+    /// Checkerboard colors, a single color is used for both squares
+    #[arg(long, num_args = 1..=2, value_name = "COLOR")]
     pub cb_color: Option<Vec<Color>>,
 
+    /// Checkerboard square width in cells [default: 2]
+    #[arg(long, value_name = "NUM", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub checker_size: Option<usize>,
+
+    /// Terminal color depth, auto detect truecolor using COLORTERM [default: auto]
+    #[arg(long, value_enum, value_name = "DEPTH")]
+    pub color_depth: Option<ColorDepth>,
+
+    /// Use only START..END of the gradient domain, stretched to the full range
+    #[arg(long, alias = "truncate", num_args = 2, allow_negative_numbers = true, value_names = ["START", "END"])]
+    pub slice: Option<Vec<f32>>,
+
+    /// Reverse the gradient
+    #[arg(short = 'r', long)]
+    pub reverse: bool,
+
+    /// Repeat the gradient N times across its domain
+    #[arg(long, value_name = "NUM")]
+    pub repeat: Option<usize>,
+
+    /// Reflect the gradient back on itself (0 -> 1 -> 0), combine with --repeat for N reflected cycles
+    #[arg(long)]
+    pub mirror: bool,
+
+    /// Simulate color vision deficiency
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub simulate: Option<Cvd>,
+
+    /// Flip the lightness of every color (Oklch L -> 1 - L), keeping hue
+    #[arg(long)]
+    pub invert_lightness: bool,
+
     /// Get N colors evenly spaced across gradient
     #[arg(short = 't', long, value_name = "NUM", conflicts_with = "sample")]
     pub take: Option<usize>,
 
-    /// Get color(s) at specific position
-    #[arg(short = 's', long, allow_negative_numbers = true, value_name = "FLOAT", num_args = 1..)]
-    pub sample: Option<Vec<f32>>,
+    /// With --take, space colors evenly by perceived difference (CIELAB delta E) instead of position
+    #[arg(long, requires = "take")]
+    pub even_perceptual: bool,
+
+    /// Where --take samples are placed across the domain [default: inclusive]
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        requires = "take",
+        conflicts_with = "even_perceptual"
+    )]
+    pub edges: Option<Edges>,
+
+    /// In a terminal, reduce --take to at most N evenly spaced colors with a notice [default: no limit]
+    #[arg(long, value_name = "NUM", requires = "take", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_colors: Option<usize>,
+
+    /// Exit with an error instead of reducing colors over --max-colors
+    #[arg(long, requires = "max_colors")]
+    pub strict: bool,
+
+    /// Get color(s) at specific position, also accepts start (min), mid, end (max)
+    #[arg(short = 's', long, allow_negative_numbers = true, value_name = "FLOAT", num_args = 1.., value_delimiter = ',')]
+    pub sample: Option<Vec<SamplePos>>,
+
+    /// Get colors at positions read from a file (- for stdin), separated by newlines, commas or spaces
+    #[arg(long, value_name = "FILE", value_parser = clap::value_parser!(PathBuf), conflicts_with_all = ["take", "sample", "json", "array", "all_formats", "sort", "dedup"])]
+    pub sample_file: Option<PathBuf>,
+
+    /// Don't clamp --sample colors to [0, 1], shows interpolation overshoot in rgb or oklab format (hex is always clamped)
+    #[arg(long)]
+    pub no_clamp: bool,
+
+    /// Warn on stderr when a --take / --sample color is out of gamut before clamping
+    #[arg(long)]
+    pub warn_oog: bool,
+
+    /// Get N colors at random positions
+    #[arg(long, value_name = "NUM", conflicts_with_all = ["take", "sample", "sample_file"], value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub random: Option<usize>,
+
+    /// Jitter the RGB of colors from --take, --sample or --random by AMOUNT (0..1)
+    #[arg(long, value_name = "AMOUNT")]
+    pub noise: Option<f32>,
+
+    /// Seed for --random, --noise and --generate, the same seed gives the same colors [default: current time]
+    #[arg(long, value_name = "NUM")]
+    pub seed: Option<u64>,
+
+    /// Sort colors from --take, --sample or --random [default: none]
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortBy>,
+
+    /// Drop colors identical to the previous one in --take, --sample or --random output
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// With --dedup, also drop colors within this CIELAB delta E of the previous one
+    #[arg(long, value_name = "DELTA_E", requires = "dedup")]
+    pub dedup_threshold: Option<f32>,
+
+    /// Print WCAG contrast ratio of each color against COLOR
+    #[arg(long, value_name = "COLOR")]
+    pub contrast: Option<Color>,
+
+    /// Keep only colors with a WCAG contrast ratio of at least 4.5 against COLOR
+    #[arg(long, value_name = "COLOR")]
+    pub accessible_on: Option<Color>,
 
     /// Output color format
     #[arg(short = 'o', long, value_enum, value_name = "FORMAT")]
     pub format: Option<OutputColor>,
 
+    /// Decimal places for float color formats [default: 2, 4 for oklab a/b and oklch chroma]
+    #[arg(long, alias = "output-precision", value_name = "NUM", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(0..=10))]
+    pub precision: Option<usize>,
+
+    /// Print hex colors without the leading '#'
+    #[arg(long)]
+    pub no_hash: bool,
+
+    /// Include alpha in hex colors (#rrggbbaa) [default: auto]
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub hex_alpha: Option<HexAlpha>,
+
+    /// Channel order of --format packed [default: rgba]
+    #[arg(long, alias = "endianness", value_enum, value_name = "ORDER")]
+    pub packed_order: Option<PackedOrder>,
+
+    /// Print hex colors in uppercase
+    #[arg(long)]
+    pub uppercase: bool,
+
     /// Print colors from --take or --sample, as array
     #[arg(short = 'a', long)]
     pub array: bool,
 
+    /// Print colors from --take or --sample in hex, rgb, hsl, hsv, hwb and oklch at once
+    #[arg(long, conflicts_with_all = ["array", "json", "format"])]
+    pub all_formats: bool,
+
+    /// Print the CIELAB delta E between every pair of --take colors, CSV when piped
+    #[arg(long, requires = "take", conflicts_with_all = ["array", "json", "all_formats", "grid"])]
+    pub distance_matrix: bool,
+
+    /// Print colors from --take or --sample as CSS custom properties --PREFIX-0, --PREFIX-1, ...
+    #[arg(long, value_name = "PREFIX", allow_hyphen_values = true, conflicts_with_all = ["array", "json", "all_formats", "distance_matrix", "grid"])]
+    pub css_vars: Option<String>,
+
+    /// Arrange colors from --take in a grid of swatches in the terminal, e.g. 4x8
+    #[arg(long, value_name = "ROWSxCOLS", requires = "take")]
+    pub grid: Option<Grid>,
+
+    /// Print colors from --take or --sample as JSON, with --list-presets a catalog of names and --take colors [default: 5]
+    #[arg(long, visible_alias = "output-json")]
+    pub json: bool,
+
+    /// Print gradient domain and a table of sampled colors (from --take or --sample) [default: 11 samples]
+    #[arg(long, conflicts_with_all = ["output", "css_output", "save_ggr"])]
+    pub info: bool,
+
+    /// Print darkest, lightest and average color, and CIELAB arclength of the gradient
+    #[arg(long, conflicts_with_all = ["output", "css_output", "save_ggr", "info"])]
+    pub stats: bool,
+
+    /// Print a histogram of the gradient hues in 12 or 36 bins, low saturation colors counted as gray
+    #[arg(long, value_name = "BINS", num_args = 0..=1, default_missing_value = "12", value_parser = PossibleValuesParser::new(["12", "36"]).map(|s| s.parse::<usize>().unwrap()), conflicts_with_all = ["output", "css_output", "save_ggr", "info", "stats"])]
+    pub hue_histogram: Option<usize>,
+
+    /// Plot each color channel across the domain as braille line charts, CSV of the samples when piped (--take sets the count) [default: 101]
+    #[arg(long, value_enum, value_name = "SPACE", conflicts_with_all = ["output", "css_output", "save_ggr", "info", "stats", "hue_histogram"])]
+    pub plot: Option<PlotSpace>,
+
+    /// Print gradient as CSS linear-gradient(), --take sets the number of stops [default: 16]
+    #[arg(long, conflicts_with_all = ["sample", "output"])]
+    pub css_output: bool,
+
+    /// Save gradient as GIMP gradient (ggr) file, --take sets the number of segments [default: 32]
+    #[arg(long, value_name = "FILE", value_parser = clap::value_parser!(PathBuf), conflicts_with_all = ["sample", "output", "css_output"])]
+    pub save_ggr: Option<PathBuf>,
+
+    /// Save gradient as SVG linearGradient, --take sets the number of stops [default: 16]
+    #[arg(long, value_name = "FILE", value_parser = clap::value_parser!(PathBuf), conflicts_with_all = ["sample", "output", "css_output", "save_ggr"])]
+    pub save_svg: Option<PathBuf>,
+
+    /// Direction for --css-output and --save-svg, e.g. 45deg or "to bottom right" [default: to right]
+    #[arg(long, value_name = "ANGLE", allow_hyphen_values = true)]
+    pub angle: Option<Angle>,
+
+    /// Print the gradient as Rust code using colorgrad
+    #[arg(long, conflicts_with_all = ["sample", "output", "css_output", "save_ggr", "save_svg"])]
+    pub emit_rust: bool,
+
+    /// Save gradient as image (png, jpg, webp), jpg is flattened onto --background or white [default size: 1000x100]
+    #[arg(long, value_name = "FILE", value_parser = clap::value_parser!(PathBuf), conflicts_with_all = ["take", "sample"])]
+    pub output: Option<PathBuf>,
+
     /// Lists all CSS named colors
     #[arg(long)]
     pub named_colors: bool,
@@ -188,3 +802,14 @@ fn verify_cli() {
     use clap::CommandFactory;
     Opt::command().debug_assert()
 }
+
+#[test]
+fn grid_size() {
+    let g: Grid = "4x8".parse().unwrap();
+    assert_eq!((g.rows, g.cols), (4, 8));
+    let g: Grid = " 2 X 3 ".parse().unwrap();
+    assert_eq!((g.rows, g.cols), (2, 3));
+    assert!("0x4".parse::<Grid>().is_err());
+    assert!("4".parse::<Grid>().is_err());
+    assert!("ax4".parse::<Grid>().is_err());
+}
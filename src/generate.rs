@@ -0,0 +1,65 @@
+use crate::util::{out_of_gamut, SplitMix64};
+use colorgrad::Color;
+
+// Hue step of 1 / golden ratio turns, consecutive hues never line up
+const GOLDEN_HUE_STEP: f32 = 360.0 * 0.618_034;
+
+// Oklch color, chroma is reduced until it fits in sRGB
+fn oklch(l: f32, c: f32, h: f32) -> Color {
+    let (sin, cos) = h.to_radians().sin_cos();
+    let mut c = c;
+    loop {
+        let col = Color::from_oklaba(l, c * cos, c * sin, 1.0);
+        if c <= 0.0 || out_of_gamut(&col).is_none() {
+            return col.clamp();
+        }
+        c -= 0.005;
+    }
+}
+
+// N seeded random colors from dark to light, hues spaced by the golden ratio and chroma
+// kept in a narrow band so they go well together
+pub fn generate_colors(seed: u64, n: usize) -> Vec<Color> {
+    let mut rng = SplitMix64::new(seed);
+
+    let hue = rng.next_f32() * 360.0;
+    let (l0, l1) = (0.35 + rng.next_f32() * 0.2, 0.75 + rng.next_f32() * 0.17);
+    let chroma = 0.08 + rng.next_f32() * 0.09;
+
+    (0..n)
+        .map(|i| {
+            let t = if n > 1 {
+                i as f32 / (n - 1) as f32
+            } else {
+                0.5
+            };
+            let l = l0 + (l1 - l0) * t + (rng.next_f32() - 0.5) * 0.04;
+            let c = chroma + (rng.next_f32() - 0.5) * 0.04;
+            let h = (hue + i as f32 * GOLDEN_HUE_STEP).rem_euclid(360.0);
+            oklch(l.clamp(0.0, 1.0), c, h)
+        })
+        .collect()
+}
+
+#[test]
+fn generated_colors() {
+    let a = generate_colors(42, 5);
+    assert_eq!(a.len(), 5);
+    assert_eq!(a, generate_colors(42, 5));
+    assert_ne!(a, generate_colors(43, 5));
+
+    // Lightness goes up, colors are in gamut
+    let l: Vec<_> = a.iter().map(|c| c.to_oklaba()[0]).collect();
+    assert!(l.windows(2).all(|w| w[0] < w[1]));
+    assert!(a.iter().all(|c| out_of_gamut(c).is_none()));
+
+    // Hues are one golden step apart
+    let hue = |c: &Color| {
+        let [_, a, b, _] = c.to_oklaba();
+        b.atan2(a).to_degrees()
+    };
+    let d = (hue(&a[1]) - hue(&a[0])).rem_euclid(360.0);
+    assert!((d - GOLDEN_HUE_STEP).abs() < 5.0);
+
+    assert_eq!(generate_colors(1, 1).len(), 1);
+}
@@ -0,0 +1,114 @@
+use std::env;
+
+#[derive(Debug, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+}
+
+// Inline image protocol of the running terminal, if known
+pub fn detect() -> Option<Protocol> {
+    let var = |key| env::var(key).unwrap_or_default();
+    detect_from(
+        &var("TERM"),
+        &var("TERM_PROGRAM"),
+        env::var_os("KITTY_WINDOW_ID").is_some(),
+    )
+}
+
+fn detect_from(term: &str, term_program: &str, kitty_window: bool) -> Option<Protocol> {
+    if kitty_window || term.contains("kitty") || term.contains("ghostty") {
+        return Some(Protocol::Kitty);
+    }
+    if matches!(term_program, "iTerm.app" | "WezTerm") {
+        return Some(Protocol::Iterm);
+    }
+    None
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+// Escape sequence showing PNG data stretched over cols x rows terminal cells
+pub fn encode(png: &[u8], protocol: Protocol, cols: usize, rows: usize) -> String {
+    let data = base64(png);
+
+    match protocol {
+        // Payload is sent in chunks of at most 4096 bytes, m=1 while more follow
+        Protocol::Kitty => {
+            let chunks: Vec<_> = data.as_bytes().chunks(4096).collect();
+            let mut s = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = (i + 1 < chunks.len()) as u8;
+                let chunk = std::str::from_utf8(chunk).unwrap();
+                if i == 0 {
+                    s.push_str(&format!(
+                        "\x1B_Ga=T,f=100,c={cols},r={rows},m={more};{chunk}\x1B\\"
+                    ));
+                } else {
+                    s.push_str(&format!("\x1B_Gm={more};{chunk}\x1B\\"));
+                }
+            }
+            s
+        }
+        Protocol::Iterm => format!(
+            "\x1B]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=0:{data}\x07",
+            png.len()
+        ),
+    }
+}
+
+#[test]
+fn inline_image() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foo"), "Zm9v");
+    assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(base64(&[0xfb, 0xff]), "+/8=");
+
+    assert_eq!(detect_from("xterm-kitty", "", false), Some(Protocol::Kitty));
+    assert_eq!(
+        detect_from("xterm-256color", "", true),
+        Some(Protocol::Kitty)
+    );
+    assert_eq!(
+        detect_from("xterm-256color", "iTerm.app", false),
+        Some(Protocol::Iterm)
+    );
+    assert_eq!(detect_from("xterm-256color", "Apple_Terminal", false), None);
+
+    assert_eq!(
+        encode(b"foo", Protocol::Iterm, 80, 2),
+        "\x1B]1337;File=inline=1;size=3;width=80;height=2;preserveAspectRatio=0:Zm9v\x07"
+    );
+    assert_eq!(
+        encode(b"foo", Protocol::Kitty, 80, 2),
+        "\x1B_Ga=T,f=100,c=80,r=2,m=0;Zm9v\x1B\\"
+    );
+
+    // 4096 base64 chars per chunk
+    let s = encode(&[0; 4000], Protocol::Kitty, 10, 1);
+    assert_eq!(s.matches("\x1B_G").count(), 2);
+    assert!(s.contains("m=1;"));
+    assert!(s.contains("\x1B_Gm=0;"));
+}
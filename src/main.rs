@@ -1,35 +1,85 @@
-use clap::Parser;
+use clap::{builder::PossibleValue, Parser, ValueEnum};
 use colorgrad::{preset, Color, Gradient};
-use std::io::{self, BufReader, IsTerminal, Write};
-use std::{ffi::OsStr, fs::File, process::exit};
+use image::ImageFormat;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::{ffi::OsStr, fs::File, path::Path, process::exit};
+
+mod adapter;
+use adapter::{
+    AlphaFrom, Ease, InvertLightness, Mix, Opacity, Remap, Repeat, Reversed, Shift, Simulate,
+    Slice, Spread, Steps,
+};
+
+mod ase;
 
 mod cli;
-use cli::{BlendMode, Interpolation, Opt, OutputColor, PRESET_NAMES};
+use cli::{
+    AlphaSource, Background, BarChar, BlendMode, ColorDepth, Cvd, Dither, Edges, Grid, HexAlpha,
+    Interpolation, Opt, OutputColor, PackedOrder, PlotSpace, SortBy, PRESET_NAMES,
+};
+
+mod generate;
+
+mod inline_image;
+
+mod median_cut;
+
+mod monotone;
+use monotone::MonotoneGradient;
+
+mod palette;
+use palette::{Palette, PaletteError};
+
+mod polar;
+use polar::PolarGradient;
+
+mod sixel;
 
 mod svg_gradient;
-use svg_gradient::parse_svg;
+use svg_gradient::{parse_svg, SpreadMethod, SvgOptions};
 
 mod util;
 
+// Upper bound for bar width when not shown on a terminal
+const MAX_WIDTH: usize = 10_000;
+
 #[derive(PartialEq)]
 enum OutputMode {
     Gradient,
     ColorsN,
     ColorsSample,
+    Image,
+    Css,
+    Ggr,
+    Svg,
+    Rust,
+    Info,
+    Stats,
+    HueHistogram,
+    Plot,
+    SampleFile,
 }
 
 struct GradientApp {
     opt: Opt,
     stdout: io::Stdout,
     is_terminal: bool,
+    // Stdout is a terminal, for --watch, --animate, --grid and --max-colors
+    interactive: bool,
+    truecolor: bool,
     output_mode: OutputMode,
     output_format: OutputColor,
     use_solid_bg: bool,
+    over_gradient: Option<Box<dyn Gradient>>,
     background: Color,
     cb_color: [Color; 2],
     term_width: usize,
     width: usize,
     height: usize,
+    label: Option<String>,
+    label_width: usize,
+    // Extra --verbose lines for the next handle_output
+    details: Vec<String>,
 }
 
 impl GradientApp {
@@ -41,14 +91,18 @@ impl GradientApp {
             None
         };
 
-        let background = if let Some(ref c) = opt.background {
-            c.clone()
-        } else {
-            Color::new(0.0, 0.0, 0.0, 1.0)
+        let background = match opt.background {
+            Some(Background::Color(ref c)) => c.clone(),
+            // Only query an interactive terminal, piped runs must not block on /dev/tty
+            Some(Background::Auto) if stdout.is_terminal() && io::stdin().is_terminal() => {
+                util::terminal_background().unwrap_or_else(|| Color::new(0.0, 0.0, 0.0, 1.0))
+            }
+            Some(Background::Auto) => Color::new(0.0, 0.0, 0.0, 1.0),
+            None => Color::new(0.0, 0.0, 0.0, 1.0),
         };
 
         let cb_color = if let Some(ref c) = opt.cb_color {
-            [c[0].clone(), c[1].clone()]
+            [c[0].clone(), c.get(1).unwrap_or(&c[0]).clone()]
         } else {
             [
                 Color::new(0.05, 0.05, 0.05, 1.0),
@@ -56,56 +110,219 @@ impl GradientApp {
             ]
         };
 
-        let width = opt
-            .width
-            .unwrap_or_else(|| term_width.unwrap_or(80))
-            .max(10)
-            .min(term_width.unwrap_or(1000));
-
-        let output_mode = if opt.take.is_some() {
+        let output_mode = if opt.output.is_some() {
+            OutputMode::Image
+        } else if opt.css_output {
+            OutputMode::Css
+        } else if opt.save_ggr.is_some() {
+            OutputMode::Ggr
+        } else if opt.save_svg.is_some() {
+            OutputMode::Svg
+        } else if opt.emit_rust {
+            OutputMode::Rust
+        } else if opt.info {
+            OutputMode::Info
+        } else if opt.stats {
+            OutputMode::Stats
+        } else if opt.hue_histogram.is_some() {
+            OutputMode::HueHistogram
+        } else if opt.plot.is_some() {
+            OutputMode::Plot
+        } else if opt.take.is_some() {
             OutputMode::ColorsN
-        } else if opt.sample.is_some() {
+        } else if opt.sample_file.is_some() {
+            OutputMode::SampleFile
+        } else if opt.sample.is_some() || opt.random.is_some() {
             OutputMode::ColorsSample
         } else {
             OutputMode::Gradient
         };
 
-        let is_terminal = stdout.is_terminal();
+        let interactive = stdout.is_terminal();
+
+        // Bars only need to fit the terminal when shown on it, not when captured
+        let fit_terminal = interactive && !opt.ansi_output && color_override() != Some(true);
+
+        let (width, height) = if output_mode == OutputMode::Image {
+            (
+                opt.width.unwrap_or(1000).max(1),
+                opt.height.unwrap_or(100).max(1),
+            )
+        } else {
+            (
+                opt.width
+                    .or_else(|| {
+                        opt.width_percent
+                            .map(|p| term_width.unwrap_or(80) * p / 100)
+                    })
+                    .unwrap_or_else(|| term_width.unwrap_or(80))
+                    .max(10)
+                    .min(match term_width {
+                        Some(w) if fit_terminal => w,
+                        _ => MAX_WIDTH,
+                    }),
+                opt.height
+                    .unwrap_or(if opt.vertical { 16 } else { 2 })
+                    .clamp(1, 50),
+            )
+        };
+
+        // With --compare, names take a column on the left of the bars
+        let mut width = width;
+        let label_width = if opt.compare && output_mode == OutputMode::Gradient {
+            let names: Vec<String> = if let Some(ref names) = opt.preset {
+                names.clone()
+            } else if let Some(ref paths) = opt.file {
+                paths.iter().map(|p| util::file_label(p)).collect()
+            } else {
+                Vec::new()
+            };
+            let lw = names
+                .iter()
+                .map(|s| s.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(width / 3);
+            if lw > 0 {
+                width -= lw + 1;
+            }
+            lw
+        } else {
+            0
+        };
+
+        // Color escapes and terminal rendering, can be forced for piped output
+        let is_terminal = color_override().unwrap_or(interactive)
+            || (opt.ansi_output && output_mode == OutputMode::Gradient);
+
+        let truecolor = match opt.color_depth {
+            Some(ColorDepth::TrueColor) => true,
+            Some(ColorDepth::Ansi256) => false,
+            _ => matches!(
+                std::env::var("COLORTERM").as_deref(),
+                Ok("truecolor") | Ok("24bit")
+            ),
+        };
 
         Self {
             output_mode,
             stdout,
             is_terminal,
+            interactive,
+            truecolor,
             use_solid_bg: opt.background.is_some(),
+            over_gradient: None,
             background,
             cb_color,
             term_width: term_width.unwrap_or(80),
             width,
-            height: opt.height.unwrap_or(2).clamp(1, 50),
+            height,
+            label: None,
+            details: Vec::new(),
+            label_width,
             output_format: opt.format.unwrap_or(OutputColor::Hex),
             opt,
         }
     }
 
     fn run(&mut self) -> io::Result<i32> {
-        if self.opt.list_presets {
+        if let Some(ref spec) = self.opt.over_gradient {
+            match over_gradient(spec) {
+                Ok(grad) => self.over_gradient = Some(grad),
+                Err(err) => {
+                    writeln!(io::stderr(), "Error: --over-gradient {spec}: {err}")?;
+                    return Ok(1);
+                }
+            }
+        }
+
+        if let Some(search) = self.opt.list_presets.clone() {
             self.width = self.term_width.min(80);
             self.height = 2;
 
-            for name in &PRESET_NAMES {
-                writeln!(self.stdout, "{name}")?;
-                self.opt.preset = Some(name.to_string());
+            let search = search.map(|s| s.to_lowercase().replace('_', "-"));
+            let names: Vec<_> = PRESET_NAMES
+                .iter()
+                .filter(|name| match search {
+                    Some(ref s) => name.contains(s.as_str()),
+                    None => true,
+                })
+                .collect();
+
+            if names.is_empty() {
+                writeln!(
+                    io::stderr(),
+                    "Error: No presets match '{}'",
+                    search.unwrap_or_default()
+                )?;
+                return Ok(1);
+            }
+
+            if self.opt.json {
+                let n = self.opt.take.unwrap_or(5).max(2);
+                let catalog: Vec<_> = names
+                    .iter()
+                    .map(|name| {
+                        let grad = preset_by_name(name).unwrap();
+                        let (dmin, dmax) = grad.domain();
+                        let colors: Vec<_> = util::linspace(dmin, dmax, n)
+                            .iter()
+                            .map(|t| grad.at(*t).clamp().to_hex_string())
+                            .collect();
+                        format!(
+                            "{{\"name\":{},\"colors\":{}}}",
+                            serde_json::Value::from(name.to_string()),
+                            serde_json::Value::from(colors)
+                        )
+                    })
+                    .collect();
+                writeln!(self.stdout, "[{}]", catalog.join(","))?;
+                return Ok(0);
+            }
+
+            for name in names {
+                if self.opt.with_colors {
+                    let grad = preset_by_name(name).unwrap();
+                    let (dmin, dmax) = grad.domain();
+                    let colors: Vec<_> = [dmin, (dmin + dmax) / 2.0, dmax]
+                        .iter()
+                        .map(|t| self.format_color(&grad.at(*t).clamp()))
+                        .collect();
+                    writeln!(self.stdout, "{name} {}", colors.join(" "))?;
+                } else {
+                    writeln!(self.stdout, "{name}")?;
+                }
+                // Plain name and colors lines when piped
+                if self.opt.with_colors && !self.is_terminal {
+                    continue;
+                }
+                self.opt.preset = Some(vec![name.to_string()]);
                 self.preset_gradient()?;
             }
 
             return Ok(0);
         }
 
+        if let Some(ref d) = self.opt.domain {
+            if d[0] >= d[1] || !d[0].is_finite() || !d[1].is_finite() {
+                writeln!(
+                    io::stderr(),
+                    "Error: Invalid domain {} {}, MIN must be less than MAX",
+                    d[0],
+                    d[1]
+                )?;
+                return Ok(1);
+            }
+        }
+
         if self.opt.named_colors {
             for (&name, &[r, g, b]) in csscolorparser::NAMED_COLORS.entries() {
+                let col = Color::from_rgba8(r, g, b, 255);
                 writeln!(
                     self.stdout,
-                    "\x1B[48;2;{r};{g};{b}m   \x1B[49;38;2;{r};{g};{b}m #{r:02x}{g:02x}{b:02x}\x1B[39m {name}"
+                    "\x1B[{}m   \x1B[49;{}m #{r:02x}{g:02x}{b:02x}\x1B[39m {name}",
+                    util::sgr_color(48, &col, self.truecolor),
+                    util::sgr_color(38, &col, self.truecolor)
                 )?;
             }
 
@@ -116,122 +333,400 @@ impl GradientApp {
             return self.preset_gradient();
         }
 
+        if self.opt.mix.is_some() {
+            return self.mix_gradient();
+        }
+
+        if self.opt.blackbody.is_some() {
+            return self.blackbody_gradient();
+        }
+
         if self.opt.custom.is_some() || self.opt.css.is_some() {
             return self.custom_gradient();
         }
 
         if self.opt.file.is_some() {
+            if self.opt.watch && self.interactive {
+                return self.watch_files();
+            }
             return self.file_gradient();
         }
 
+        if self.opt.from_image.is_some() {
+            return self.image_gradient();
+        }
+
+        if self.opt.generate {
+            return self.generated_gradient();
+        }
+
         self.example_help()?;
         Ok(1)
     }
 
     fn preset_gradient(&mut self) -> io::Result<i32> {
-        let grad: Box<dyn Gradient> = match self
-            .opt
-            .preset
-            .as_ref()
-            .unwrap()
-            .to_lowercase()
-            .replace('-', "_")
-            .as_ref()
-        {
-            "blues" => Box::new(preset::blues()),
-            "br_bg" => Box::new(preset::br_bg()),
-            "bu_gn" => Box::new(preset::bu_gn()),
-            "bu_pu" => Box::new(preset::bu_pu()),
-            "cividis" => Box::new(preset::cividis()),
-            "cool" => Box::new(preset::cool()),
-            "cubehelix" => Box::new(preset::cubehelix_default()),
-            "gn_bu" => Box::new(preset::gn_bu()),
-            "greens" => Box::new(preset::greens()),
-            "greys" => Box::new(preset::greys()),
-            "inferno" => Box::new(preset::inferno()),
-            "magma" => Box::new(preset::magma()),
-            "or_rd" => Box::new(preset::or_rd()),
-            "oranges" => Box::new(preset::oranges()),
-            "pi_yg" => Box::new(preset::pi_yg()),
-            "plasma" => Box::new(preset::plasma()),
-            "pr_gn" => Box::new(preset::pr_gn()),
-            "pu_bu" => Box::new(preset::pu_bu()),
-            "pu_bu_gn" => Box::new(preset::pu_bu_gn()),
-            "pu_or" => Box::new(preset::pu_or()),
-            "pu_rd" => Box::new(preset::pu_rd()),
-            "purples" => Box::new(preset::purples()),
-            "rainbow" => Box::new(preset::rainbow()),
-            "rd_bu" => Box::new(preset::rd_bu()),
-            "rd_gy" => Box::new(preset::rd_gy()),
-            "rd_pu" => Box::new(preset::rd_pu()),
-            "rd_yl_bu" => Box::new(preset::rd_yl_bu()),
-            "rd_yl_gn" => Box::new(preset::rd_yl_gn()),
-            "reds" => Box::new(preset::reds()),
-            "sinebow" => Box::new(preset::sinebow()),
-            "spectral" => Box::new(preset::spectral()),
-            "turbo" => Box::new(preset::turbo()),
-            "viridis" => Box::new(preset::viridis()),
-            "warm" => Box::new(preset::warm()),
-            "yl_gn" => Box::new(preset::yl_gn()),
-            "yl_gn_bu" => Box::new(preset::yl_gn_bu()),
-            "yl_or_br" => Box::new(preset::yl_or_br()),
-            "yl_or_rd" => Box::new(preset::yl_or_rd()),
+        let names = self.opt.preset.clone().unwrap();
+        let mut status = 0;
+
+        for name in &names {
+            let grad = match preset_by_name(name) {
+                Some(grad) => grad,
+                None => {
+                    if let Some(similar) = util::suggest(name, &PRESET_NAMES) {
+                        writeln!(
+                            io::stderr(),
+                            "Error: Invalid preset gradient name '{name}', did you mean '{similar}'?"
+                        )?;
+                    } else {
+                        writeln!(io::stderr(), "Error: Invalid preset gradient name '{name}'. Use -l flag to list all preset gradient names.")?;
+                    }
+                    status = 1;
+                    continue;
+                }
+            };
+
+            self.label = Some(name.clone());
+
+            if names.len() > 1 && self.label_width == 0 && !self.opt.quiet {
+                if self.is_terminal {
+                    writeln!(self.stdout, "\x1B[1m{name}\x1B[0m")?;
+                } else {
+                    writeln!(self.stdout, "{name}")?;
+                }
+            }
+
+            if self.output_mode == OutputMode::Rust {
+                let name = match name.to_lowercase().replace('-', "_").as_ref() {
+                    "cubehelix" => "cubehelix_default".to_string(),
+                    s => s.to_string(),
+                };
+                writeln!(self.stdout, "colorgrad::preset::{name}()")?;
+                continue;
+            }
+
+            if self.handle_output(grad)? != 0 {
+                status = 1;
+            }
+        }
+
+        Ok(status)
+    }
+
+    fn mix_gradient(&mut self) -> io::Result<i32> {
+        let args = self.opt.mix.clone().unwrap();
+
+        let ratio = match args[2].parse::<f32>() {
+            Ok(r) if r.is_finite() => r,
             _ => {
-                writeln!(io::stderr(), "Error: Invalid preset gradient name. Use -l flag to list all preset gradient names.")?;
+                writeln!(io::stderr(), "Error: Invalid mix ratio '{}'", args[2])?;
                 return Ok(1);
             }
         };
 
-        self.handle_output(grad)?;
-        Ok(0)
+        let mut grads = Vec::with_capacity(2);
+
+        for name in &args[..2] {
+            match preset_by_name(name) {
+                Some(grad) => grads.push(grad),
+                None => {
+                    writeln!(io::stderr(), "Error: Invalid preset gradient name '{name}'")?;
+                    return Ok(1);
+                }
+            }
+        }
+
+        let b = grads.pop().unwrap();
+        let a = grads.pop().unwrap();
+        self.handle_output(Box::new(Mix::new(a, b, ratio)))
     }
 
-    fn custom_gradient(&mut self) -> io::Result<i32> {
-        let mut gb = colorgrad::GradientBuilder::new();
+    fn blackbody_gradient(&mut self) -> io::Result<i32> {
+        let k = self.opt.blackbody.clone().unwrap();
+        let (min, max) = (k[0], k[1]);
+        let range = 1667.0..=25000.0;
+        if min >= max || !range.contains(&min) || !range.contains(&max) {
+            writeln!(
+                io::stderr(),
+                "Error: Invalid blackbody range {min} {max}, expected MIN_K < MAX_K within 1667..25000"
+            )?;
+            return Ok(1);
+        }
 
-        if let Some(ref css_gradient) = self.opt.css {
-            gb.css(css_gradient);
+        self.label = Some(format!("{min}K-{max}K"));
+        self.verbose(format!("kelvin: {min} .. {max}"));
+        self.handle_output(Box::new(util::Blackbody::new(min, max)))
+    }
+
+    fn custom_gradient(&mut self) -> io::Result<i32> {
+        let mode = self.opt.blend_mode.unwrap_or(BlendMode::Oklab);
+        let interpolation = self.opt.interpolation.unwrap_or(Interpolation::CatmullRom);
+
+        let css = self.opt.css.as_deref().map(util::css_stops);
+
+        let grad = if let Some(ref css_gradient) = css {
+            if matches!(
+                mode,
+                BlendMode::Oklch | BlendMode::Hsluv | BlendMode::Hsl | BlendMode::Hsv
+            ) {
+                Err("blend mode oklch, hsluv, hsl and hsv are not supported with --css".to_string())
+            } else {
+                let mut gb = colorgrad::GradientBuilder::new();
+                gb.css(css_gradient);
+                self.build_gradient(&mut gb, mode, interpolation)
+            }
+        } else if let Some(msg) = self.position_mismatch() {
+            Err(msg)
         } else {
-            gb.colors(self.opt.custom.as_ref().unwrap());
+            self.colors_gradient(
+                self.opt.custom.as_ref().unwrap(),
+                self.opt.position.as_deref().or(self.opt.domain.as_deref()),
+                mode,
+                interpolation,
+            )
+        };
 
-            if let Some(ref pos) = self.opt.position {
-                gb.domain(pos);
+        let grad = match grad {
+            Ok(g) => g,
+            Err(e) => {
+                writeln!(io::stderr(), "Custom gradient error: {e}")?;
+                return Ok(1);
             }
+        };
+
+        if self.output_mode == OutputMode::Rust {
+            let setup = if let Some(ref css_gradient) = css {
+                format!(".css({css_gradient:?})")
+            } else {
+                util::rust_colors(
+                    self.opt.custom.as_ref().unwrap(),
+                    self.opt.position.as_deref().or(self.opt.domain.as_deref()),
+                )
+            };
+            return self.emit_rust(util::rust_builder(&setup, mode, interpolation));
         }
 
-        gb.mode(match self.opt.blend_mode {
-            Some(BlendMode::Rgb) => colorgrad::BlendMode::Rgb,
-            Some(BlendMode::LinearRgb) => colorgrad::BlendMode::LinearRgb,
-            Some(BlendMode::Lab) => colorgrad::BlendMode::Lab,
-            _ => colorgrad::BlendMode::Oklab,
-        });
+        match css {
+            Some(ref css_gradient) => {
+                self.verbose(format!("css: {css_gradient}"));
+                self.verbose_blend(None, mode, interpolation);
+            }
+            None => {
+                let n = self.opt.custom.as_ref().unwrap().len();
+                self.verbose_blend(Some(n), mode, interpolation);
+            }
+        }
 
-        let grad: Box<dyn Gradient> = match self.opt.interpolation {
-            Some(Interpolation::Linear) => match gb.build::<colorgrad::LinearGradient>() {
-                Ok(g) => Box::new(g),
-                Err(e) => {
-                    writeln!(io::stderr(), "Custom gradient error: {e}")?;
-                    return Ok(1);
-                }
-            },
-            Some(Interpolation::Basis) => match gb.build::<colorgrad::BasisGradient>() {
-                Ok(g) => Box::new(g),
-                Err(e) => {
-                    writeln!(io::stderr(), "Custom gradient error: {e}")?;
-                    return Ok(1);
-                }
-            },
-            _ => match gb.build::<colorgrad::CatmullRomGradient>() {
-                Ok(g) => Box::new(g),
-                Err(e) => {
-                    writeln!(io::stderr(), "Custom gradient error: {e}")?;
-                    return Ok(1);
+        self.handle_output(grad)
+    }
+
+    fn verbose(&mut self, line: String) {
+        if self.opt.verbose {
+            self.details.push(line);
+        }
+    }
+
+    fn verbose_blend(
+        &mut self,
+        colors: Option<usize>,
+        mode: BlendMode,
+        interpolation: Interpolation,
+    ) {
+        let name =
+            |v: Option<PossibleValue>| v.map(|v| v.get_name().to_string()).unwrap_or_default();
+        self.verbose(format!("blend mode: {}", name(mode.to_possible_value())));
+        self.verbose(format!(
+            "interpolation: {}",
+            name(interpolation.to_possible_value())
+        ));
+        if let Some(n) = colors {
+            self.verbose(format!("colors: {n}"));
+        }
+    }
+
+    // --position needs one value per color, or two for the start and end
+    fn position_mismatch(&self) -> Option<String> {
+        let n = self.opt.custom.as_ref()?.len();
+        let p = self.opt.position.as_ref()?.len();
+        if p == n || p == 2 {
+            return None;
+        }
+        Some(format!(
+            "--position has {p} values but --custom has {n} colors"
+        ))
+    }
+
+    fn image_gradient(&mut self) -> io::Result<i32> {
+        let path = self.opt.from_image.clone().unwrap();
+        let img = match image::open(&path) {
+            Ok(img) => img.to_rgba8(),
+            Err(e) => {
+                writeln!(io::stderr(), "Error: {}: {e}", path.display())?;
+                return Ok(1);
+            }
+        };
+
+        // Skip transparent pixels, sample large images sparsely
+        let stride = (img.pixels().len() / 250_000).max(1);
+        let pixels: Vec<[u8; 3]> = img
+            .pixels()
+            .step_by(stride)
+            .filter(|px| px[3] >= 128)
+            .map(|px| [px[0], px[1], px[2]])
+            .collect();
+
+        let mut colors = median_cut::dominant_colors(&pixels, self.opt.colors.unwrap_or(5));
+        if colors.is_empty() {
+            writeln!(io::stderr(), "Error: {}: no opaque pixels", path.display())?;
+            return Ok(1);
+        }
+        let mut pos = vec![0.0; colors.len()];
+        util::sort_colors(&mut colors, &mut pos, SortBy::Luminance);
+
+        let mode = self.opt.blend_mode.unwrap_or(BlendMode::Oklab);
+        let interpolation = self.opt.interpolation.unwrap_or(Interpolation::CatmullRom);
+        self.label = Some(util::file_label(&path));
+
+        match self.colors_gradient(&colors, None, mode, interpolation) {
+            Ok(_) if self.output_mode == OutputMode::Rust => {
+                let setup = util::rust_colors(&colors, None);
+                self.emit_rust(util::rust_builder(&setup, mode, interpolation))
+            }
+            Ok(grad) => {
+                self.verbose_blend(Some(colors.len()), mode, interpolation);
+                self.handle_output(grad)
+            }
+            Err(err) => {
+                writeln!(io::stderr(), "Error: {}: {err}", path.display())?;
+                Ok(1)
+            }
+        }
+    }
+
+    fn generated_gradient(&mut self) -> io::Result<i32> {
+        let seed = self.opt.seed.unwrap_or_else(util::time_seed);
+        let colors = generate::generate_colors(seed, self.opt.colors.unwrap_or(5));
+
+        let mode = self.opt.blend_mode.unwrap_or(BlendMode::Oklab);
+        let interpolation = self.opt.interpolation.unwrap_or(Interpolation::CatmullRom);
+        self.label = Some(format!("seed {seed}"));
+
+        match self.colors_gradient(&colors, None, mode, interpolation) {
+            Ok(_) if self.output_mode == OutputMode::Rust => {
+                let setup = util::rust_colors(&colors, None);
+                self.emit_rust(util::rust_builder(&setup, mode, interpolation))
+            }
+            Ok(grad) => {
+                self.verbose_blend(Some(colors.len()), mode, interpolation);
+                self.handle_output(grad)
+            }
+            Err(err) => {
+                writeln!(io::stderr(), "Error: {err}")?;
+                Ok(1)
+            }
+        }
+    }
+
+    fn emit_rust(&mut self, code: Result<String, String>) -> io::Result<i32> {
+        match code {
+            Ok(code) => {
+                writeln!(self.stdout, "{code}")?;
+                Ok(0)
+            }
+            Err(err) => {
+                writeln!(io::stderr(), "Error: {err}")?;
+                Ok(1)
+            }
+        }
+    }
+
+    fn colors_gradient(
+        &self,
+        colors: &[Color],
+        pos: Option<&[f32]>,
+        mode: BlendMode,
+        interpolation: Interpolation,
+    ) -> Result<Box<dyn Gradient>, String> {
+        let space = match mode {
+            BlendMode::Oklch => polar::Space::Oklch,
+            BlendMode::Hsluv => polar::Space::Hsluv,
+            BlendMode::Hsl => polar::Space::Hsl,
+            BlendMode::Hsv => polar::Space::Hsv,
+            _ if interpolation == Interpolation::Monotone => {
+                return Ok(Box::new(MonotoneGradient::new(colors, pos, mode)?));
+            }
+            _ => {
+                let mut gb = colorgrad::GradientBuilder::new();
+                gb.colors(colors);
+
+                if let Some(pos) = pos {
+                    gb.domain(pos);
                 }
-            },
+
+                return self.build_gradient(&mut gb, mode, interpolation);
+            }
         };
 
-        self.handle_output(grad)?;
-        Ok(0)
+        let grad = Box::new(PolarGradient::new(colors, pos, space)?);
+        if interpolation == Interpolation::Bezier {
+            return Ok(Box::new(Ease::new(grad, self.bezier_params())));
+        }
+        Ok(grad)
+    }
+
+    fn bezier_params(&self) -> [f32; 4] {
+        self.opt.bezier_params.unwrap_or_default().0
+    }
+
+    fn build_gradient(
+        &self,
+        gb: &mut colorgrad::GradientBuilder,
+        mode: BlendMode,
+        interpolation: Interpolation,
+    ) -> Result<Box<dyn Gradient>, String> {
+        gb.mode(match mode {
+            BlendMode::Rgb => colorgrad::BlendMode::Rgb,
+            BlendMode::LinearRgb => colorgrad::BlendMode::LinearRgb,
+            BlendMode::Lab => colorgrad::BlendMode::Lab,
+            _ => colorgrad::BlendMode::Oklab,
+        });
+
+        let err = |e: colorgrad::GradientBuilderError| e.to_string();
+
+        Ok(match interpolation {
+            Interpolation::Linear => {
+                Box::new(gb.build::<colorgrad::LinearGradient>().map_err(err)?)
+            }
+            Interpolation::Basis => Box::new(gb.build::<colorgrad::BasisGradient>().map_err(err)?),
+            Interpolation::CatmullRom => {
+                Box::new(gb.build::<colorgrad::CatmullRomGradient>().map_err(err)?)
+            }
+            Interpolation::Monotone => {
+                // Let the builder parse and validate the stops, then use them
+                gb.build::<colorgrad::LinearGradient>().map_err(err)?;
+                Box::new(MonotoneGradient::new(
+                    gb.get_colors(),
+                    Some(gb.get_positions()),
+                    mode,
+                )?)
+            }
+            Interpolation::Bezier => {
+                let grad = gb.build::<colorgrad::LinearGradient>().map_err(err)?;
+                Box::new(Ease::new(Box::new(grad), self.bezier_params()))
+            }
+        })
+    }
+
+    // Print file name & gradient name headers
+    fn show_info(&self) -> bool {
+        if self.opt.quiet || self.label_width > 0 {
+            return false;
+        }
+        self.is_terminal
+            || (self.output_mode == OutputMode::Gradient)
+            || (self.output_mode == OutputMode::Info)
+            || (self.output_mode == OutputMode::Stats)
+            || (self.output_mode == OutputMode::HueHistogram)
+            || (self.output_mode == OutputMode::Plot)
     }
 
     fn file_gradient(&mut self) -> io::Result<i32> {
@@ -250,6 +745,9 @@ impl GradientApp {
         let mut status = 0;
 
         for path in self.opt.file.as_ref().unwrap().clone() {
+            self.label = Some(util::file_label(&path));
+            self.details.clear();
+
             if !path.exists() {
                 write!(
                     self.stdout,
@@ -263,27 +761,54 @@ impl GradientApp {
             if let Some(ext) = path.extension().and_then(OsStr::to_str) {
                 match ext.to_lowercase().as_ref() {
                     "ggr" => {
-                        if self.is_terminal || (self.output_mode == OutputMode::Gradient) {
+                        if self.show_info() {
                             write!(self.stdout, "{}", &path.display())?;
                         }
 
-                        let f = File::open(&path)?;
+                        let content = std::fs::read(&path)?;
+
+                        // Segment count is the third line of a ggr file
+                        let segments = content
+                            .split(|&b| b == b'\n')
+                            .nth(2)
+                            .and_then(|s| std::str::from_utf8(s).ok())
+                            .and_then(|s| s.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
 
                         match colorgrad::GimpGradient::new(
-                            BufReader::new(f),
+                            content.as_slice(),
                             &ggr_fg_color,
                             &ggr_bg_color,
                         ) {
                             Ok(grad) => {
-                                if self.is_terminal || (self.output_mode == OutputMode::Gradient) {
+                                if self.show_info() {
                                     writeln!(self.stdout, " \x1B[1m{}\x1B[0m", grad.name())?;
                                 }
 
-                                self.handle_output(Box::new(grad))?;
+                                if self.output_mode == OutputMode::Info {
+                                    writeln!(self.stdout, "segments: {segments}")?;
+                                }
+
+                                self.verbose(format!("segments: {segments}"));
+
+                                if self.output_mode == OutputMode::Rust {
+                                    writeln!(
+                                        self.stdout,
+                                        "colorgrad::GimpGradient::new(\n    std::io::BufReader::new(std::fs::File::open({:?})?),\n    &colorgrad::Color::from_html({:?})?,\n    &colorgrad::Color::from_html({:?})?,\n)?",
+                                        path.display().to_string(),
+                                        ggr_fg_color.to_hex_string(),
+                                        ggr_bg_color.to_hex_string()
+                                    )?;
+                                    continue;
+                                }
+
+                                if self.handle_output(Box::new(grad))? != 0 {
+                                    status = 1;
+                                }
                             }
 
                             Err(err) => {
-                                if self.is_terminal || (self.output_mode == OutputMode::Gradient) {
+                                if self.show_info() {
                                     writeln!(self.stdout, "\n  \x1B[31m{err}\x1B[39m")?;
                                 }
                             }
@@ -292,20 +817,29 @@ impl GradientApp {
 
                     "svg" => {
                         let filename = &path.display().to_string();
-                        let gradients =
-                            parse_svg(path.into_os_string().into_string().unwrap().as_ref());
-
-                        if (self.is_terminal || (self.output_mode == OutputMode::Gradient))
-                            && gradients.is_empty()
-                        {
+                        let label = util::file_label(&path);
+                        let svg_opt = SvgOptions {
+                            strict: self.opt.strict_svg,
+                            current_color: self
+                                .opt
+                                .svg_current_color
+                                .clone()
+                                .unwrap_or_else(|| Color::new(0.0, 0.0, 0.0, 1.0)),
+                        };
+                        let gradients = parse_svg(
+                            path.into_os_string().into_string().unwrap().as_ref(),
+                            &svg_opt,
+                        );
+
+                        if self.show_info() && gradients.is_empty() {
                             writeln!(self.stdout, "{filename}")?;
                             writeln!(self.stdout, "  \x1B[31mNo gradients.\x1B[39m")?;
                         }
 
-                        for (grad, id) in gradients {
-                            let (id, stop) = if let Some(id) = id {
+                        for g in gradients {
+                            let (id, stop) = if let Some(ref id) = g.id {
                                 if let Some(ref id2) = self.opt.svg_id {
-                                    if &id == id2 {
+                                    if id == id2 {
                                         (format!("#{id}"), true)
                                     } else {
                                         continue;
@@ -317,110 +851,1192 @@ impl GradientApp {
                                 ("".to_string(), false)
                             };
 
-                            if self.is_terminal || (self.output_mode == OutputMode::Gradient) {
+                            if self.show_info() {
                                 writeln!(self.stdout, "{filename} \x1B[1m{id}\x1B[0m")?;
                             }
 
-                            self.handle_output(Box::new(grad))?;
+                            self.label = Some(format!("{label}{id}"));
+                            self.details.clear();
+
+                            if let Some(ref transform) = g.transform {
+                                writeln!(
+                                    io::stderr(),
+                                    "Warning: {filename} {id}: gradientTransform=\"{transform}\" ignored"
+                                )?;
+                            }
+
+                            // SVG gradients are linear RGB unless asked otherwise
+                            let mode = self.opt.blend_mode.unwrap_or(BlendMode::Rgb);
+                            let interpolation =
+                                self.opt.interpolation.unwrap_or(Interpolation::Linear);
+
+                            let grad = match g.error {
+                                Some(ref err) => Err(err.clone()),
+                                None => self.colors_gradient(
+                                    &g.colors,
+                                    Some(&g.pos),
+                                    mode,
+                                    interpolation,
+                                ),
+                            };
+
+                            let res = match grad {
+                                Ok(_) if self.output_mode == OutputMode::Rust => {
+                                    let setup = util::rust_colors(&g.colors, Some(&g.pos));
+                                    self.emit_rust(util::rust_builder(&setup, mode, interpolation))?
+                                }
+                                Ok(grad) => {
+                                    if !id.is_empty() {
+                                        self.verbose(format!("id: {id}"));
+                                    }
+                                    if let Some(ref geo) = g.radial {
+                                        self.verbose(format!("radial: {}", geo.describe()));
+                                    }
+                                    let grad: Box<dyn Gradient> = match g.spread {
+                                        SpreadMethod::Pad => grad,
+                                        method => {
+                                            let name = format!("{method:?}").to_lowercase();
+                                            self.verbose(format!("spread: {name}"));
+                                            Box::new(Spread::new(grad, method))
+                                        }
+                                    };
+                                    let offsets: Vec<_> =
+                                        g.pos.iter().map(|p| p.to_string()).collect();
+                                    self.verbose(format!("offsets: {}", offsets.join(", ")));
+                                    self.verbose_blend(Some(g.colors.len()), mode, interpolation);
+                                    self.handle_output(grad)?
+                                }
+                                Err(err) => {
+                                    if self.show_info() {
+                                        writeln!(self.stdout, "  \x1B[31m{err}\x1B[39m")?;
+                                    } else {
+                                        writeln!(io::stderr(), "{filename} {id}: {err}")?;
+                                    }
+                                    1
+                                }
+                            };
+
+                            if res != 0 {
+                                status = 1;
+                            }
 
                             if stop {
                                 break;
                             }
                         }
                     }
-                    _ => continue,
-                }
-            }
-        }
+                    "gpl" => {
+                        let f = File::open(&path)?;
+                        let pal = palette::parse_gpl(BufReader::new(f));
 
-        Ok(status)
-    }
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
+                    }
 
-    fn handle_output(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
-        match self.output_mode {
-            OutputMode::Gradient => self.display_gradient(grad),
+                    "map" => {
+                        let f = File::open(&path)?;
+                        let pal = palette::parse_map(BufReader::new(f));
 
-            OutputMode::ColorsN => {
-                let mut colors = grad.colors(self.opt.take.unwrap());
-                if self.use_solid_bg {
-                    for col in &mut colors {
-                        util::blend_on(col, &self.background);
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
                     }
-                }
-                self.display_colors(&colors)
-            }
 
-            OutputMode::ColorsSample => {
-                let mut colors = Vec::with_capacity(self.opt.sample.as_ref().unwrap().len());
-                for pos in self.opt.sample.as_ref().unwrap().iter() {
-                    let mut col = grad.at(*pos).clamp();
-                    if self.use_solid_bg {
-                        util::blend_on(&mut col, &self.background);
+                    "json" => {
+                        let pal = palette::parse_json(&std::fs::read_to_string(&path)?);
+
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
                     }
-                    colors.push(col);
-                }
-                self.display_colors(&colors)
-            }
-        }
-    }
 
-    fn display_gradient(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
-        let (dmin, dmax) = grad.domain();
-        let w2 = (self.width * 2 - 1) as f32;
-        let [cb_0, cb_1] = &self.cb_color;
+                    "act" => {
+                        let pal = palette::parse_act(&std::fs::read(&path)?);
 
-        for y in 0..self.height {
-            let mut i = 0;
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
+                    }
 
-            for x in 0..self.width {
-                let bg_color = if self.use_solid_bg {
-                    &self.background
-                } else if ((x / 2) & 1) ^ (y & 1) == 0 {
-                    cb_0
-                } else {
-                    cb_1
-                };
+                    "ase" => {
+                        let pal = ase::parse_ase(&std::fs::read(&path)?);
 
-                let col_l = grad.at(util::remap(i as f32, 0.0, w2, dmin, dmax));
-                i += 1;
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
+                    }
 
-                let col_r = grad.at(util::remap(i as f32, 0.0, w2, dmin, dmax));
-                i += 1;
+                    "txt" | "hex" | "csv" => {
+                        let f = File::open(&path)?;
+                        let pal = palette::parse_text(BufReader::new(f));
 
-                let col_l = util::blend_color(&col_l, bg_color).to_rgba8();
-                let col_r = util::blend_color(&col_r, bg_color).to_rgba8();
+                        if self.palette_gradient(&path, pal)? != 0 {
+                            status = 1;
+                        }
+                    }
 
-                write!(
-                    self.stdout,
-                    "\x1B[38;2;{};{};{};48;2;{};{};{}m\u{258C}",
-                    col_l[0], col_l[1], col_l[2], col_r[0], col_r[1], col_r[2]
-                )?;
+                    _ => continue,
+                }
             }
-
-            writeln!(self.stdout, "\x1B[39;49m")?;
         }
 
-        Ok(0)
+        Ok(status)
     }
 
-    fn display_colors(&mut self, colors: &[Color]) -> io::Result<i32> {
-        if self.opt.array {
+    // Poll modification times and redraw on change, the process ends with Ctrl-C
+    fn watch_files(&mut self) -> io::Result<i32> {
+        let delay = std::time::Duration::from_millis(250);
+        let mut last = None;
+
+        loop {
+            let mtimes: Vec<_> = self
+                .opt
+                .file
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|path| path.metadata().and_then(|m| m.modified()).ok())
+                .collect();
+
+            if last.as_ref() != Some(&mtimes) {
+                write!(self.stdout, "\x1B[2J\x1B[H")?;
+                self.file_gradient()?;
+                self.stdout.flush()?;
+                last = Some(mtimes);
+            }
+
+            std::thread::sleep(delay);
+        }
+    }
+
+    fn palette_gradient(
+        &mut self,
+        path: &Path,
+        palette: Result<Palette, PaletteError>,
+    ) -> io::Result<i32> {
+        let show_info = self.show_info();
+
+        if show_info {
+            write!(self.stdout, "{}", path.display())?;
+        }
+
+        let pal = match palette {
+            Ok(pal) => pal,
+            Err(err) => {
+                if show_info {
+                    writeln!(self.stdout, "\n  \x1B[31m{err}\x1B[39m")?;
+                } else {
+                    writeln!(io::stderr(), "{}: {err}", path.display())?;
+                }
+                return Ok(1);
+            }
+        };
+
+        if show_info {
+            writeln!(
+                self.stdout,
+                " \x1B[1m{}\x1B[0m",
+                pal.name.as_deref().unwrap_or_default()
+            )?;
+        }
+
+        let mode = self.opt.blend_mode.unwrap_or(BlendMode::Oklab);
+        let interpolation = self.opt.interpolation.unwrap_or(Interpolation::CatmullRom);
+        let grad = self.colors_gradient(&pal.colors, pal.pos.as_deref(), mode, interpolation);
+
+        match grad {
+            Ok(_) if self.output_mode == OutputMode::Rust => {
+                let setup = util::rust_colors(&pal.colors, pal.pos.as_deref());
+                self.emit_rust(util::rust_builder(&setup, mode, interpolation))
+            }
+            Ok(grad) => {
+                self.verbose_blend(Some(pal.colors.len()), mode, interpolation);
+                self.handle_output(grad)
+            }
+            Err(err) => {
+                writeln!(io::stderr(), "{}: {err}", path.display())?;
+                Ok(1)
+            }
+        }
+    }
+
+    fn handle_output(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let details = std::mem::take(&mut self.details);
+        if self.opt.verbose {
+            let (dmin, dmax) = grad.domain();
+            let mut stderr = io::stderr();
+            writeln!(stderr, "{}", self.label.as_deref().unwrap_or("gradient"))?;
+            writeln!(stderr, "  domain: {dmin} .. {dmax}")?;
+            for line in details {
+                writeln!(stderr, "  {line}")?;
+            }
+        }
+
+        let grad: Box<dyn Gradient> = match self.opt.domain.as_deref() {
+            Some(&[dmin, dmax]) if grad.domain() != (dmin, dmax) => {
+                Box::new(Remap::new(grad, dmin, dmax))
+            }
+            _ => grad,
+        };
+
+        let grad: Box<dyn Gradient> = match self.opt.slice.as_deref() {
+            Some(&[start, end]) => {
+                let (dmin, dmax) = grad.domain();
+                if start >= end || start < dmin || end > dmax {
+                    writeln!(
+                        io::stderr(),
+                        "Error: invalid slice {start}..{end}, expected START < END within the domain {dmin}..{dmax}"
+                    )?;
+                    return Ok(1);
+                }
+                Box::new(Slice::new(grad, start, end))
+            }
+            _ => grad,
+        };
+
+        let grad: Box<dyn Gradient> = if self.opt.reverse {
+            Box::new(Reversed::new(grad))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if self.opt.repeat.is_some() || self.opt.mirror {
+            let n = self.opt.repeat.unwrap_or(1).max(1);
+            Box::new(Repeat::new(grad, n, self.opt.mirror))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if self.opt.invert_lightness {
+            Box::new(InvertLightness::new(grad))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if let Some(source) = self.opt.alpha_from {
+            Box::new(AlphaFrom::new(grad, source))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if let Some(opacity) = self.opt.opacity {
+            Box::new(Opacity::new(grad, opacity))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if let Some(kind) = self.opt.simulate {
+            Box::new(Simulate::new(grad, kind))
+        } else {
+            grad
+        };
+
+        let grad: Box<dyn Gradient> = if let Some(size) = self.opt.lut_size {
+            Box::new(util::Lut::new(&*grad, size))
+        } else {
+            grad
+        };
+
+        // Posterize only the rendered bar / image
+        let grad: Box<dyn Gradient> = match self.opt.steps {
+            Some(n) if matches!(self.output_mode, OutputMode::Gradient | OutputMode::Image) => {
+                Box::new(Steps::new(grad, n))
+            }
+            _ => grad,
+        };
+
+        match self.output_mode {
+            OutputMode::Gradient if self.opt.animate && self.interactive => self.animate(grad),
+
+            OutputMode::Gradient if self.opt.sixel => self.display_sixel(grad),
+
+            OutputMode::Gradient if self.opt.braille && self.is_terminal => {
+                self.display_braille(grad)
+            }
+
+            OutputMode::Gradient if self.opt.inline_image && self.is_terminal => {
+                match inline_image::detect() {
+                    Some(protocol) => self.display_inline_image(grad, protocol),
+                    None => self.display_gradient(grad),
+                }
+            }
+
+            OutputMode::Gradient => self.display_gradient(grad),
+
+            OutputMode::ColorsN => {
+                let mut n = self.opt.take.unwrap();
+                match self.opt.max_colors {
+                    Some(max) if n > max && self.interactive => {
+                        if self.opt.strict {
+                            writeln!(
+                                io::stderr(),
+                                "Error: --take {n} is more than --max-colors {max}"
+                            )?;
+                            return Ok(1);
+                        }
+                        writeln!(
+                            io::stderr(),
+                            "Note: --take {n} reduced to {max} colors (--max-colors)"
+                        )?;
+                        n = max;
+                    }
+                    _ => {}
+                }
+                let (dmin, dmax) = grad.domain();
+                let (mut positions, mut colors) = if self.opt.even_perceptual {
+                    let positions = util::perceptual_positions(&*grad, n);
+                    let colors = positions
+                        .iter()
+                        .map(|t| self.sample_at(&*grad, *t))
+                        .collect();
+                    (positions, colors)
+                } else {
+                    let positions = match self.opt.edges {
+                        Some(Edges::Centered) => util::cell_centers(dmin, dmax, n),
+                        _ => util::linspace(dmin, dmax, n),
+                    };
+                    let colors: Vec<_> = positions.iter().map(|t| grad.at(*t)).collect();
+                    if self.opt.warn_oog {
+                        for (t, col) in positions.iter().zip(&colors) {
+                            util::warn_out_of_gamut(*t, col);
+                        }
+                    }
+                    (positions, colors)
+                };
+                if self.use_solid_bg {
+                    for col in &mut colors {
+                        util::blend_on(col, &self.background);
+                    }
+                }
+                if let Some(amount) = self.opt.noise {
+                    let seed = self.opt.seed.unwrap_or_else(util::time_seed);
+                    util::jitter_colors(&mut colors, amount, seed);
+                }
+                if let Some(ref bg) = self.opt.accessible_on {
+                    util::filter_contrast(&mut colors, &mut positions, bg, 4.5);
+                    if colors.is_empty() {
+                        writeln!(
+                            io::stderr(),
+                            "Error: no colors with contrast >= 4.5 against {}",
+                            bg.to_hex_string()
+                        )?;
+                        return Ok(1);
+                    }
+                }
+                if self.opt.dedup {
+                    util::dedup_colors(&mut colors, &mut positions, self.opt.dedup_threshold);
+                }
+                if let Some(by) = self.opt.sort {
+                    util::sort_colors(&mut colors, &mut positions, by);
+                }
+                if self.opt.premultiply {
+                    colors.iter_mut().for_each(|c| *c = util::premultiply(c));
+                }
+                self.display_colors(&colors, &positions)
+            }
+
+            OutputMode::ColorsSample => {
+                let (dmin, dmax) = grad.domain();
+                let mut positions: Vec<f32> = if let Some(n) = self.opt.random {
+                    let seed = self.opt.seed.unwrap_or_else(util::time_seed);
+                    let mut rng = util::SplitMix64::new(seed);
+                    (0..n)
+                        .map(|_| util::remap(rng.next_f32(), 0.0, 1.0, dmin, dmax))
+                        .collect()
+                } else {
+                    self.opt
+                        .sample
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|p| p.resolve(dmin, dmax))
+                        .collect()
+                };
+                let mut colors = Vec::with_capacity(positions.len());
+                for pos in &positions {
+                    let mut col = self.sample_at(&*grad, *pos);
+                    if self.use_solid_bg {
+                        util::blend_on(&mut col, &self.background);
+                    }
+                    colors.push(col);
+                }
+                if let Some(amount) = self.opt.noise {
+                    let seed = self.opt.seed.unwrap_or_else(util::time_seed);
+                    util::jitter_colors(&mut colors, amount, seed);
+                }
+                if let Some(ref bg) = self.opt.accessible_on {
+                    util::filter_contrast(&mut colors, &mut positions, bg, 4.5);
+                    if colors.is_empty() {
+                        writeln!(
+                            io::stderr(),
+                            "Error: no colors with contrast >= 4.5 against {}",
+                            bg.to_hex_string()
+                        )?;
+                        return Ok(1);
+                    }
+                }
+                if self.opt.dedup {
+                    util::dedup_colors(&mut colors, &mut positions, self.opt.dedup_threshold);
+                }
+                if let Some(by) = self.opt.sort {
+                    util::sort_colors(&mut colors, &mut positions, by);
+                }
+                if self.opt.premultiply {
+                    colors.iter_mut().for_each(|c| *c = util::premultiply(c));
+                }
+                self.display_colors(&colors, &positions)
+            }
+
+            OutputMode::Image => self.save_image(grad),
+
+            OutputMode::Css => self.display_css(grad),
+
+            OutputMode::Ggr => self.save_ggr(grad),
+
+            OutputMode::Svg => self.save_svg(grad),
+
+            OutputMode::Rust => {
+                writeln!(
+                    io::stderr(),
+                    "Error: --emit-rust is not supported for this gradient"
+                )?;
+                Ok(1)
+            }
+
+            OutputMode::Info => {
+                let (dmin, dmax) = grad.domain();
+                let positions = if let Some(ref pos) = self.opt.sample {
+                    pos.iter().map(|p| p.resolve(dmin, dmax)).collect()
+                } else {
+                    util::linspace(dmin, dmax, self.opt.take.unwrap_or(11))
+                };
+                self.display_info(grad, &positions)
+            }
+
+            OutputMode::Stats => self.display_stats(grad),
+            OutputMode::HueHistogram => self.display_hue_histogram(grad),
+            OutputMode::Plot => self.display_plot(grad),
+
+            OutputMode::SampleFile => self.sample_file(grad),
+        }
+    }
+
+    fn display_info(&mut self, grad: Box<dyn Gradient>, positions: &[f32]) -> io::Result<i32> {
+        let (dmin, dmax) = grad.domain();
+        writeln!(self.stdout, "domain: {dmin} .. {dmax}")?;
+        writeln!(self.stdout, "stops: {}", positions.len())?;
+
+        for pos in positions {
+            let mut col = self.sample_at(&*grad, *pos);
+            if self.use_solid_bg {
+                util::blend_on(&mut col, &self.background);
+            }
+            let col = util::apply_gamma(&col, self.opt.gamma.unwrap_or(1.0));
+            writeln!(
+                self.stdout,
+                "{pos:.4} -> {}",
+                util::format_color(&col, self.output_format, self.opt.precision)
+            )?;
+        }
+
+        Ok(0)
+    }
+
+    // Color at t, clamped to [0, 1] unless --no-clamp
+    fn sample_at(&self, grad: &dyn Gradient, t: f32) -> Color {
+        let col = grad.at(t);
+        if self.opt.warn_oog {
+            util::warn_out_of_gamut(t, &col);
+        }
+        if self.opt.no_clamp {
+            col
+        } else {
+            col.clamp()
+        }
+    }
+
+    // Positions are read and colors written line by line, so large files are fine
+    fn sample_file(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let path = self.opt.sample_file.clone().unwrap();
+        let reader: Box<dyn BufRead> = if path.as_os_str() == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            match File::open(&path) {
+                Ok(f) => Box::new(BufReader::new(f)),
+                Err(err) => {
+                    writeln!(io::stderr(), "Error: {}: {err}", path.display())?;
+                    return Ok(1);
+                }
+            }
+        };
+
+        let gamma = self.opt.gamma.unwrap_or(1.0);
+
+        for (i, line) in reader.lines().enumerate() {
+            let positions = match util::parse_floats(&line?) {
+                Ok(v) => v,
+                Err(err) => {
+                    writeln!(
+                        io::stderr(),
+                        "Error: {} (line {}): {err}",
+                        path.display(),
+                        i + 1
+                    )?;
+                    return Ok(1);
+                }
+            };
+
+            for pos in positions {
+                let mut col = self.sample_at(&*grad, pos);
+                if self.use_solid_bg {
+                    util::blend_on(&mut col, &self.background);
+                }
+                let col = util::apply_gamma(&col, gamma);
+                writeln!(self.stdout, "{}", self.format_color(&col))?;
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn display_stats(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let stats = util::gradient_stats(&*grad);
+
+        for (label, col) in [
+            ("darkest", &stats.darkest),
+            ("lightest", &stats.lightest),
+            ("average", &stats.mean),
+        ] {
+            let mut col = col.clone();
+            if self.use_solid_bg {
+                util::blend_on(&mut col, &self.background);
+            }
+            let col = util::apply_gamma(&col, self.opt.gamma.unwrap_or(1.0));
+            if self.is_terminal {
+                writeln!(
+                    self.stdout,
+                    "{label:<9} {} {}",
+                    util::fmt_color(&col, &self.cb_color, 4, self.truecolor),
+                    self.format_color(&col)
+                )?;
+            } else {
+                writeln!(self.stdout, "{label}: {}", self.format_color(&col))?;
+            }
+        }
+
+        writeln!(self.stdout, "arclength: {:.2}", stats.arclength)?;
+        Ok(0)
+    }
+
+    fn display_hue_histogram(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let (counts, gray) = util::hue_histogram(&*grad, self.opt.hue_histogram.unwrap());
+        let total = (counts.iter().sum::<usize>() + gray).max(1) as f32;
+        let max = counts.iter().copied().max().unwrap_or(0).max(gray).max(1) as f32;
+        let bar_width = self.width.saturating_sub(20).max(10) as f32;
+        let step = 360 / counts.len();
+
+        let rows = counts
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (format!("{}°", i * step), Some(i * step), *n))
+            .chain([("gray".to_string(), None, gray)]);
+
+        for (label, hue, n) in rows {
+            let bar = "#".repeat((n as f32 / max * bar_width).round() as usize);
+            let pct = n as f32 / total * 100.0;
+            if self.is_terminal {
+                let col = match hue {
+                    Some(h) => Color::from_hsva(h as f32, 1.0, 1.0, 1.0),
+                    None => Color::new(0.5, 0.5, 0.5, 1.0),
+                };
+                writeln!(
+                    self.stdout,
+                    "{label:>5} {} {pct:5.1}% {bar}",
+                    util::fmt_color(&col, &self.cb_color, 2, self.truecolor),
+                )?;
+            } else {
+                writeln!(self.stdout, "{label:>5} {pct:5.1}% {bar}")?;
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn display_plot(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let space = self.opt.plot.unwrap();
+        let (dmin, dmax) = grad.domain();
+
+        if !self.is_terminal {
+            let n = self.opt.take.unwrap_or(101).max(2);
+            let names = util::plot_channels(&grad.at(dmin), space).map(|c| c.0);
+            writeln!(self.stdout, "t,{}", names.join(","))?;
+            for t in util::linspace(dmin, dmax, n) {
+                let values = util::plot_channels(&grad.at(t), space).map(|c| c.3.to_string());
+                writeln!(self.stdout, "{t},{}", values.join(","))?;
+            }
+            return Ok(0);
+        }
+
+        let width = self.width.saturating_sub(8).max(10);
+        let samples: Vec<_> = util::linspace(dmin, dmax, width * 2)
+            .iter()
+            .map(|t| util::plot_channels(&grad.at(*t), space))
+            .collect();
+
+        for i in 0..4 {
+            let (name, lo, hi, _) = samples[0][i];
+            let values: Vec<f32> = samples.iter().map(|s| s[i].3).collect();
+            // Widen the range to show overshoot
+            let min = values.iter().fold(lo, |a, &b| a.min(b));
+            let max = values.iter().fold(hi, |a, &b| a.max(b));
+
+            writeln!(self.stdout, "\x1B[1m{name}\x1B[0m {min:.2}..{max:.2}")?;
+            for row in util::braille_plot(&values, min, max, 4) {
+                writeln!(self.stdout, "        {row}")?;
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn save_ggr(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let path = self.opt.save_ggr.as_ref().unwrap();
+        let colors = grad.colors(self.opt.take.unwrap_or(32).max(2));
+
+        let name = path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .unwrap_or("gradient");
+
+        if let Err(err) = std::fs::write(path, util::to_ggr(&colors, name)) {
+            writeln!(
+                io::stderr(),
+                "Error: Failed to save ggr '{}': {err}",
+                path.display()
+            )?;
+            return Ok(1);
+        }
+
+        Ok(0)
+    }
+
+    fn save_svg(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let path = self.opt.save_svg.as_ref().unwrap();
+        let n = self.opt.take.unwrap_or(16).max(2);
+        let colors = grad.colors(n);
+        let offsets = util::linspace(0.0, 1.0, n);
+
+        let angle = self.opt.angle.as_ref().map_or(90.0, |a| a.degrees);
+
+        if let Err(err) = std::fs::write(path, util::to_svg(&colors, &offsets, angle)) {
+            writeln!(
+                io::stderr(),
+                "Error: Failed to save svg '{}': {err}",
+                path.display()
+            )?;
+            return Ok(1);
+        }
+
+        Ok(0)
+    }
+
+    fn display_css(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let n = self.opt.take.unwrap_or(16);
+        let (dmin, dmax) = grad.domain();
+        let mut stops = Vec::with_capacity(n);
+
+        for (mut col, pos) in grad
+            .colors(n)
+            .into_iter()
+            .zip(util::linspace(dmin, dmax, n))
+        {
+            if self.use_solid_bg {
+                util::blend_on(&mut col, &self.background);
+            }
+            let pct = util::remap(pos, dmin, dmax, 0.0, 100.0);
+            stops.push(format!(
+                "{} {}%",
+                util::format_color(&col, self.output_format, self.opt.precision),
+                (pct * 100.0).round() / 100.0
+            ));
+        }
+
+        let angle = self
+            .opt
+            .angle
+            .as_ref()
+            .map_or("to right", |a| a.css.as_str());
+        writeln!(
+            self.stdout,
+            "linear-gradient({angle}, {})",
+            stops.join(", ")
+        )?;
+        Ok(0)
+    }
+
+    fn display_sixel(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        // Roughly the size of the half-block bar, a cell is about 8x16 pixels
+        let (w, h) = (self.width as u32 * 8, self.height as u32 * 16);
+        let mut img = util::gradient_image(&*grad, w, h);
+
+        // No transparency in sixel
+        self.composite_background(&mut img);
+
+        writeln!(self.stdout, "{}", sixel::encode(&img))?;
+        Ok(0)
+    }
+
+    fn display_inline_image(
+        &mut self,
+        grad: Box<dyn Gradient>,
+        protocol: inline_image::Protocol,
+    ) -> io::Result<i32> {
+        let (w, h) = (self.width as u32 * 8, self.height as u32 * 16);
+        let mut img = util::gradient_image(&*grad, w, h);
+        self.composite_background(&mut img);
+
+        let png = util::encode_png(&img).map_err(io::Error::other)?;
+        writeln!(
+            self.stdout,
+            "{}",
+            inline_image::encode(&png, protocol, self.width, self.height)
+        )?;
+        Ok(0)
+    }
+
+    // Composite an image onto the background or checkerboard, a cell is about 8x16 pixels
+    fn composite_background(&self, img: &mut image::RgbaImage) {
+        for (x, y, px) in img.enumerate_pixels_mut() {
+            let bg = if self.use_solid_bg {
+                &self.background
+            } else {
+                &self.cb_color[(((x / 16) + (y / 16)) & 1) as usize]
+            };
+            let col = Color::from_rgba8(px[0], px[1], px[2], px[3]);
+            *px = image::Rgba(self.blend(&col, bg).to_rgba8());
+        }
+    }
+
+    fn save_image(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let path = self.opt.output.as_ref().unwrap();
+
+        let ext = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|s| s.to_lowercase());
+
+        let format = match ext.as_deref() {
+            Some("png") => ImageFormat::Png,
+            Some("jpg") | Some("jpeg") => ImageFormat::Jpeg,
+            Some("webp") => ImageFormat::WebP,
+            _ => {
+                writeln!(
+                    io::stderr(),
+                    "Error: Unsupported image format '{}'. Supported formats: png, jpg, webp.",
+                    path.display()
+                )?;
+                return Ok(1);
+            }
+        };
+
+        let mut imgbuf = util::gradient_image(&*grad, self.width as u32, self.height as u32);
+
+        if self.opt.premultiply && format != ImageFormat::Jpeg {
+            util::premultiply_image(&mut imgbuf);
+        }
+
+        let res = if format == ImageFormat::Jpeg {
+            // No alpha channel in JPEG
+            let bg = if self.use_solid_bg {
+                self.background.clone()
+            } else {
+                Color::new(1.0, 1.0, 1.0, 1.0)
+            };
+            util::flatten_image(&mut imgbuf, &bg);
+            image::DynamicImage::ImageRgba8(imgbuf)
+                .to_rgb8()
+                .save_with_format(path, format)
+        } else if format == ImageFormat::Png {
+            util::encode_png(&imgbuf)
+                .and_then(|png| std::fs::write(path, png).map_err(image::ImageError::IoError))
+        } else {
+            imgbuf.save_with_format(path, format)
+        };
+
+        if let Err(err) = res {
+            writeln!(
+                io::stderr(),
+                "Error: Failed to save image '{}': {err}",
+                path.display()
+            )?;
+            return Ok(1);
+        }
+
+        Ok(0)
+    }
+
+    // Solid background or checkerboard square at cell x, row y
+    fn bg_color(&self, x: usize, y: usize) -> &Color {
+        if self.use_solid_bg {
+            return &self.background;
+        }
+        let size = self.opt.checker_size.unwrap_or(2);
+        &self.cb_color[((x / size) & 1) ^ (y & 1)]
+    }
+
+    // Background at relative position t of the bar: the --over-gradient color composited
+    // onto the solid background or checkerboard
+    fn bg_at(&self, x: usize, y: usize, t: f32) -> Color {
+        let bg = self.bg_color(x, y);
+        match self.over_gradient {
+            Some(ref over) => {
+                let (dmin, dmax) = over.domain();
+                util::blend_color(&over.at(dmin + (dmax - dmin) * t).clamp(), bg)
+            }
+            None => bg.clone(),
+        }
+    }
+
+    // Blend color on background, then apply display gamma
+    fn blend(&self, col: &Color, bg: &Color) -> Color {
+        util::apply_gamma(&util::blend_color(col, bg), self.opt.gamma.unwrap_or(1.0))
+    }
+
+    fn animate(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let delay = std::time::Duration::from_secs_f32(1.0 / self.opt.fps.unwrap_or(30) as f32);
+        // One cell per frame
+        let step = 1.0 / self.width as f32;
+        let frames = self.opt.frames.unwrap_or(usize::MAX);
+
+        write!(self.stdout, "\x1B[2J")?;
+
+        for frame in 0..frames {
+            write!(self.stdout, "\x1B[H")?;
+            let phase = (frame as f32 * step).fract();
+            self.display_gradient(Box::new(Shift::new(grad.clone(), phase)))?;
+            self.stdout.flush()?;
+            std::thread::sleep(delay);
+        }
+
+        Ok(0)
+    }
+
+    fn display_gradient(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        if self.opt.vertical {
+            return self.display_gradient_vertical(grad);
+        }
+
+        let (dmin, dmax) = grad.domain();
+        let w1 = (self.width - 1).max(1) as f32;
+        let w2 = (self.width * 2 - 1) as f32;
+        let style = self.opt.bar_char.unwrap_or(BarChar::Half);
+
+        for y in 0..self.height {
+            if self.label_width > 0 {
+                let label = match self.label {
+                    Some(ref s) if y == 0 => util::fit_label(s, self.label_width),
+                    _ => " ".repeat(self.label_width),
+                };
+                write!(self.stdout, "{label} ")?;
+            }
+
+            let mut i = 0;
+            let mut row = Vec::with_capacity(self.width * 2);
+
+            for x in 0..self.width {
+                if style != BarChar::Half {
+                    let col = grad.at(util::remap(x as f32, 0.0, w1, dmin, dmax));
+                    row.push(self.blend(&col, &self.bg_at(x, y, x as f32 / w1)));
+                    continue;
+                }
+
+                let col_l = grad.at(util::remap(i as f32, 0.0, w2, dmin, dmax));
+                row.push(self.blend(&col_l, &self.bg_at(x, y, i as f32 / w2)));
+                i += 1;
+
+                let col_r = grad.at(util::remap(i as f32, 0.0, w2, dmin, dmax));
+                row.push(self.blend(&col_r, &self.bg_at(x, y, i as f32 / w2)));
+                i += 1;
+            }
+
+            if !self.truecolor {
+                if let Some(method) = self.opt.dither {
+                    row = util::dither(&row, method, y);
+                }
+            }
+
+            if style != BarChar::Half {
+                for col in &row {
+                    write!(
+                        self.stdout,
+                        "{}",
+                        util::bar_cell(col, style, self.truecolor)
+                    )?;
+                }
+            } else {
+                for c in row.chunks(2) {
+                    write!(
+                        self.stdout,
+                        "\x1B[{};{}m\u{258C}",
+                        util::sgr_color(38, &c[0], self.truecolor),
+                        util::sgr_color(48, &c[1], self.truecolor)
+                    )?;
+                }
+            }
+
+            writeln!(self.stdout, "\x1B[39;49m")?;
+        }
+
+        if self.opt.ruler && self.is_terminal {
+            let (ticks, labels) = util::ruler(self.width, dmin, dmax);
+            let pad = " ".repeat(self.label_width + usize::from(self.label_width > 0));
+            writeln!(self.stdout, "{pad}{ticks}\n{pad}{labels}")?;
+        }
+
+        Ok(0)
+    }
+
+    fn display_braille(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let (dmin, dmax) = grad.domain();
+        let w2 = (self.width * 2 - 1) as f32;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // Left and right dot column
+                let [l, r] = [x * 2, x * 2 + 1].map(|i| {
+                    let col = grad.at(util::remap(i as f32, 0.0, w2, dmin, dmax));
+                    self.blend(&col, &self.bg_at(x, y, i as f32 / w2))
+                });
+                let dots = util::braille_cell(l.to_oklaba()[0], r.to_oklaba()[0]);
+                write!(
+                    self.stdout,
+                    "\x1B[{}m{dots}",
+                    util::sgr_color(38, &l.interpolate_rgb(&r, 0.5), self.truecolor)
+                )?;
+            }
+            writeln!(self.stdout, "\x1B[39m")?;
+        }
+
+        Ok(0)
+    }
+
+    fn display_gradient_vertical(&mut self, grad: Box<dyn Gradient>) -> io::Result<i32> {
+        let (dmin, dmax) = grad.domain();
+        let h1 = (self.height - 1).max(1) as f32;
+        let h2 = (self.height * 2 - 1) as f32;
+        let style = self.opt.bar_char.unwrap_or(BarChar::Half);
+
+        for y in 0..self.height {
+            let col_t = grad.at(util::remap((y * 2) as f32, 0.0, h2, dmin, dmax));
+            let col_b = grad.at(util::remap((y * 2 + 1) as f32, 0.0, h2, dmin, dmax));
+            let col = grad.at(util::remap(y as f32, 0.0, h1, dmin, dmax));
+
+            for x in 0..self.width {
+                if style != BarChar::Half {
+                    let bg_color = self.bg_at(x, y, y as f32 / h1);
+                    let cell = util::bar_cell(&self.blend(&col, &bg_color), style, self.truecolor);
+                    write!(self.stdout, "{cell}")?;
+                    continue;
+                }
+
+                let col_t = self.blend(&col_t, &self.bg_at(x, y, (y * 2) as f32 / h2));
+                let col_b = self.blend(&col_b, &self.bg_at(x, y, (y * 2 + 1) as f32 / h2));
+
+                write!(
+                    self.stdout,
+                    "\x1B[{};{}m\u{2580}",
+                    util::sgr_color(38, &col_t, self.truecolor),
+                    util::sgr_color(48, &col_b, self.truecolor)
+                )?;
+            }
+
+            writeln!(self.stdout, "\x1B[39;49m")?;
+        }
+
+        Ok(0)
+    }
+
+    // Color label for --take / --sample output
+    fn format_color(&self, col: &Color) -> String {
+        self.format_color_as(col, self.output_format)
+    }
+
+    fn format_color_as(&self, col: &Color, format: OutputColor) -> String {
+        if format == OutputColor::Hex {
+            return util::format_hex(
+                col,
+                self.opt.hex_alpha.unwrap_or(HexAlpha::Auto),
+                !self.opt.no_hash,
+                self.opt.uppercase,
+            );
+        }
+        if format == OutputColor::Packed {
+            return util::format_packed(col, self.opt.packed_order.unwrap_or(PackedOrder::Rgba));
+        }
+        util::format_color(col, format, self.opt.precision)
+    }
+
+    fn display_grid(&mut self, colors: &[Color], grid: Grid) -> io::Result<i32> {
+        if colors.len() > grid.rows * grid.cols {
+            writeln!(
+                io::stderr(),
+                "Error: {} colors do not fit in a {}x{} grid",
+                colors.len(),
+                grid.rows,
+                grid.cols
+            )?;
+            return Ok(1);
+        }
+
+        let labels: Vec<String> = colors.iter().map(|c| self.format_color(c)).collect();
+        let wc = labels.iter().map(|s| s.chars().count()).max().unwrap_or(7);
+
+        // Trailing cells are left blank
+        for (row, cols) in colors.chunks(grid.cols).zip(labels.chunks(grid.cols)) {
+            let swatches: Vec<String> = row
+                .iter()
+                .map(|c| util::fmt_color(c, &self.cb_color, wc, self.truecolor))
+                .collect();
+            let labels: Vec<String> = cols.iter().map(|s| format!("{s:<wc$}")).collect();
+            writeln!(self.stdout, "{}\n{}", swatches.join(" "), labels.join(" "))?;
+        }
+
+        Ok(0)
+    }
+
+    fn display_distance_matrix(&mut self, colors: &[Color]) -> io::Result<i32> {
+        let matrix = util::distance_matrix(colors);
+
+        if !self.is_terminal {
+            let labels: Vec<_> = colors
+                .iter()
+                .map(|c| util::csv_field(&self.format_color(c)))
+                .collect();
+            writeln!(self.stdout, ",{}", labels.join(","))?;
+            for (label, row) in labels.iter().zip(&matrix) {
+                let row: Vec<_> = row.iter().map(|d| format!("{d:.2}")).collect();
+                writeln!(self.stdout, "{label},{}", row.join(","))?;
+            }
+            return Ok(0);
+        }
+
+        // Swatch header, then one row per color, 7 chars per cell
+        let swatches: Vec<_> = colors
+            .iter()
+            .map(|c| util::fmt_color(c, &self.cb_color, 6, self.truecolor))
+            .collect();
+        writeln!(self.stdout, "   {}", swatches.join(" "))?;
+
+        for (col, row) in colors.iter().zip(&matrix) {
+            let cells: Vec<_> = row.iter().map(|d| format!("{d:>6.1}")).collect();
+            writeln!(
+                self.stdout,
+                "{} {}",
+                util::fmt_color(col, &self.cb_color, 2, self.truecolor),
+                cells.join(" ")
+            )?;
+        }
+
+        Ok(0)
+    }
+
+    fn display_colors(&mut self, colors: &[Color], positions: &[f32]) -> io::Result<i32> {
+        let gamma = self.opt.gamma.unwrap_or(1.0);
+        let colors: Vec<Color> = colors.iter().map(|c| util::apply_gamma(c, gamma)).collect();
+        let colors = &colors[..];
+
+        if self.opt.json {
+            let items: Vec<String> = colors
+                .iter()
+                .zip(positions)
+                .map(|(col, pos)| util::json_color(col, *pos))
+                .collect();
+            writeln!(self.stdout, "[{}]", items.join(","))?;
+            return Ok(0);
+        }
+
+        if self.opt.array {
             let mut cols = Vec::with_capacity(colors.len());
             for col in colors {
-                cols.push(util::format_color(col, self.output_format));
+                cols.push(self.format_color(col));
             }
             writeln!(self.stdout, "{cols:?}")?;
             return Ok(0);
         }
 
+        if self.opt.distance_matrix {
+            return self.display_distance_matrix(colors);
+        }
+
+        if let Some(ref prefix) = self.opt.css_vars {
+            let prefix = prefix.trim_start_matches('-');
+            for (i, col) in colors.iter().enumerate() {
+                writeln!(self.stdout, "--{prefix}-{i}: {};", self.format_color(col))?;
+            }
+            return Ok(0);
+        }
+
+        if self.opt.all_formats {
+            const FORMATS: [OutputColor; 6] = [
+                OutputColor::Hex,
+                OutputColor::Rgb255,
+                OutputColor::Hsl,
+                OutputColor::Hsv,
+                OutputColor::Hwb,
+                OutputColor::Oklch,
+            ];
+
+            for (i, col) in colors.iter().enumerate() {
+                let labels: Vec<String> = FORMATS
+                    .iter()
+                    .map(|f| self.format_color_as(col, *f))
+                    .collect();
+
+                if self.is_terminal {
+                    writeln!(
+                        self.stdout,
+                        "{} {}",
+                        util::fmt_color(col, &self.cb_color, 7, self.truecolor),
+                        labels.join("  ")
+                    )?;
+                } else {
+                    if i > 0 {
+                        writeln!(self.stdout)?;
+                    }
+                    for label in labels {
+                        writeln!(self.stdout, "{label}")?;
+                    }
+                }
+            }
+            return Ok(0);
+        }
+
         if self.is_terminal {
-            if self.output_format != OutputColor::Hex {
+            if let Some(grid) = self.opt.grid.filter(|_| self.interactive) {
+                return self.display_grid(colors, grid);
+            }
+
+            if self.output_format != OutputColor::Hex || self.opt.contrast.is_some() {
                 for col in colors {
+                    let mut label = self.format_color(col);
+                    if self.output_format == OutputColor::Name {
+                        label = format!("{label} {}", col.to_hex_string());
+                    }
+                    if let Some(ref c) = self.opt.contrast {
+                        let ratio = util::contrast_ratio(col, c);
+                        let mark = if ratio >= 4.5 {
+                            "\x1B[32mAA \u{2714}\x1B[39m"
+                        } else {
+                            "\x1B[31mAA \u{2718}\x1B[39m"
+                        };
+                        label = format!("{label} {ratio:.2}:1 {mark}");
+                    }
                     writeln!(
                         self.stdout,
                         "{} {}",
-                        util::fmt_color(col, &self.cb_color, 7),
-                        util::format_color(col, self.output_format)
+                        util::fmt_color(col, &self.cb_color, 7, self.truecolor),
+                        label
                     )?;
                 }
                 return Ok(0);
@@ -432,9 +2048,9 @@ impl GradientApp {
             let mut w = 0;
 
             for (i, col) in colors.iter().enumerate() {
-                let hex = util::format_color(col, self.output_format);
+                let hex = self.format_color(col);
                 let wc = hex.len();
-                buff0.push_str(&util::fmt_color(col, &self.cb_color, wc));
+                buff0.push_str(&util::fmt_color(col, &self.cb_color, wc, self.truecolor));
                 buff1.push_str(&hex);
                 w += wc;
                 if w < self.term_width {
@@ -445,7 +2061,7 @@ impl GradientApp {
                 let nwc = if i == last {
                     0
                 } else {
-                    util::format_color(&colors[i + 1], self.output_format).len()
+                    self.format_color(&colors[i + 1]).len()
                 };
 
                 if w + nwc > self.term_width || i == last {
@@ -459,11 +2075,16 @@ impl GradientApp {
         }
 
         for col in colors {
-            writeln!(
-                self.stdout,
-                "{}",
-                util::format_color(col, self.output_format)
-            )?;
+            if let Some(ref c) = self.opt.contrast {
+                writeln!(
+                    self.stdout,
+                    "{} {:.2}",
+                    self.format_color(col),
+                    util::contrast_ratio(col, c)
+                )?;
+                continue;
+            }
+            writeln!(self.stdout, "{}", self.format_color(col))?;
         }
         Ok(0)
     }
@@ -491,7 +2112,7 @@ impl GradientApp {
         )?;
         writeln!(self.stdout, "{}", bold("EXAMPLES:"))?;
         writeln!(self.stdout, "{prompt} gradient --preset rainbow")?;
-        self.opt.preset = Some("rainbow".to_string());
+        self.opt.preset = Some(vec!["rainbow".to_string()]);
         self.preset_gradient()?;
 
         writeln!(
@@ -531,7 +2152,7 @@ impl GradientApp {
 
         writeln!(self.stdout, "{prompt} gradient --preset viridis --take 10")?;
         self.opt.custom = None;
-        self.opt.preset = Some("viridis".to_string());
+        self.opt.preset = Some(vec!["viridis".to_string()]);
         self.opt.take = Some(10);
         self.output_mode = OutputMode::ColorsN;
         self.preset_gradient()?;
@@ -540,6 +2161,78 @@ impl GradientApp {
     }
 }
 
+// Preset name or CSS gradient for --over-gradient
+fn over_gradient(spec: &str) -> Result<Box<dyn Gradient>, String> {
+    if let Some(grad) = preset_by_name(spec) {
+        return Ok(grad);
+    }
+    let grad = colorgrad::GradientBuilder::new()
+        .css(&util::css_stops(spec))
+        .build::<colorgrad::LinearGradient>()
+        .map_err(|e| e.to_string())?;
+    Ok(Box::new(grad))
+}
+
+fn preset_by_name(name: &str) -> Option<Box<dyn Gradient>> {
+    Some(match name.to_lowercase().replace('-', "_").as_ref() {
+        "blues" => Box::new(preset::blues()),
+        "br_bg" => Box::new(preset::br_bg()),
+        "bu_gn" => Box::new(preset::bu_gn()),
+        "bu_pu" => Box::new(preset::bu_pu()),
+        "cividis" => Box::new(preset::cividis()),
+        "cool" => Box::new(preset::cool()),
+        "cubehelix" => Box::new(preset::cubehelix_default()),
+        "gn_bu" => Box::new(preset::gn_bu()),
+        "greens" => Box::new(preset::greens()),
+        "greys" => Box::new(preset::greys()),
+        "inferno" => Box::new(preset::inferno()),
+        "magma" => Box::new(preset::magma()),
+        "or_rd" => Box::new(preset::or_rd()),
+        "oranges" => Box::new(preset::oranges()),
+        "pi_yg" => Box::new(preset::pi_yg()),
+        "plasma" => Box::new(preset::plasma()),
+        "pr_gn" => Box::new(preset::pr_gn()),
+        "pu_bu" => Box::new(preset::pu_bu()),
+        "pu_bu_gn" => Box::new(preset::pu_bu_gn()),
+        "pu_or" => Box::new(preset::pu_or()),
+        "pu_rd" => Box::new(preset::pu_rd()),
+        "purples" => Box::new(preset::purples()),
+        "rainbow" => Box::new(preset::rainbow()),
+        "rd_bu" => Box::new(preset::rd_bu()),
+        "rd_gy" => Box::new(preset::rd_gy()),
+        "rd_pu" => Box::new(preset::rd_pu()),
+        "rd_yl_bu" => Box::new(preset::rd_yl_bu()),
+        "rd_yl_gn" => Box::new(preset::rd_yl_gn()),
+        "reds" => Box::new(preset::reds()),
+        "sinebow" => Box::new(preset::sinebow()),
+        "spectral" => Box::new(preset::spectral()),
+        "turbo" => Box::new(preset::turbo()),
+        "viridis" => Box::new(preset::viridis()),
+        "warm" => Box::new(preset::warm()),
+        "yl_gn" => Box::new(preset::yl_gn()),
+        "yl_gn_bu" => Box::new(preset::yl_gn_bu()),
+        "yl_or_br" => Box::new(preset::yl_or_br()),
+        "yl_or_rd" => Box::new(preset::yl_or_rd()),
+        _ => return None,
+    })
+}
+
+// Colored output forced on or off by NO_COLOR, CLICOLOR_FORCE or CLICOLOR=0,
+// None to decide by whether stdout is a terminal
+fn color_override() -> Option<bool> {
+    let var = |key| std::env::var_os(key).filter(|v| !v.is_empty());
+    if var("NO_COLOR").is_some() {
+        return Some(false);
+    }
+    if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+        return Some(true);
+    }
+    if var("CLICOLOR").is_some_and(|v| v == "0") {
+        return Some(false);
+    }
+    None
+}
+
 fn main() {
     let opt = Opt::parse();
 
@@ -0,0 +1,97 @@
+use colorgrad::Color;
+
+// Spread of each RGB channel in a set of pixels
+fn channel_ranges(pixels: &[[u8; 3]]) -> [u8; 3] {
+    let mut lo = [255u8; 3];
+    let mut hi = [0u8; 3];
+    for px in pixels {
+        for k in 0..3 {
+            lo[k] = lo[k].min(px[k]);
+            hi[k] = hi[k].max(px[k]);
+        }
+    }
+    [
+        hi[0].saturating_sub(lo[0]),
+        hi[1].saturating_sub(lo[1]),
+        hi[2].saturating_sub(lo[2]),
+    ]
+}
+
+// Up to n dominant colors by median cut: the box with the widest channel is split at
+// the median of that channel until there are n boxes, each box gives its mean color.
+pub fn dominant_colors(pixels: &[[u8; 3]], n: usize) -> Vec<Color> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![pixels.to_vec()];
+
+    while boxes.len() < n {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let r = channel_ranges(b);
+                let k = (0..3).max_by_key(|&k| r[k]).unwrap();
+                (i, k, r[k])
+            })
+            .filter(|(_, _, r)| *r > 0)
+            .max_by_key(|(_, _, r)| *r);
+
+        let Some((i, k, _)) = widest else {
+            break;
+        };
+
+        let mut b = boxes.swap_remove(i);
+        b.sort_unstable_by_key(|px| px[k]);
+        let upper = b.split_off(b.len() / 2);
+        boxes.push(b);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0u64; 3];
+            for px in b {
+                for k in 0..3 {
+                    sum[k] += px[k] as u64;
+                }
+            }
+            let n = b.len() as u64;
+            Color::from_rgba8(
+                ((sum[0] + n / 2) / n) as u8,
+                ((sum[1] + n / 2) / n) as u8,
+                ((sum[2] + n / 2) / n) as u8,
+                255,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn median_cut() {
+    let mut pixels = Vec::new();
+    for px in [[0, 0, 0], [255, 0, 0], [0, 0, 255], [255, 255, 255]] {
+        pixels.extend(vec![px; 25]);
+    }
+
+    let mut hex: Vec<_> = dominant_colors(&pixels, 4)
+        .iter()
+        .map(|c| c.to_hex_string())
+        .collect();
+    hex.sort();
+    assert_eq!(hex, ["#000000", "#0000ff", "#ff0000", "#ffffff"]);
+
+    // Mean color of each box
+    let hex: Vec<_> = dominant_colors(&pixels[..50], 1)
+        .iter()
+        .map(|c| c.to_hex_string())
+        .collect();
+    assert_eq!(hex, ["#800000"]);
+
+    // Fewer distinct colors than requested
+    assert_eq!(dominant_colors(&pixels[..25], 4).len(), 1);
+    assert!(dominant_colors(&[], 5).is_empty());
+}
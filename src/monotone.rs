@@ -0,0 +1,193 @@
+use crate::BlendMode;
+use colorgrad::{Color, Gradient};
+
+fn to_space(col: &Color, mode: BlendMode) -> [f32; 4] {
+    match mode {
+        BlendMode::Rgb => [col.r, col.g, col.b, col.a],
+        BlendMode::LinearRgb => col.to_linear_rgba(),
+        BlendMode::Lab => col.to_laba(),
+        _ => col.to_oklaba(),
+    }
+}
+
+fn from_space(v: [f32; 4], mode: BlendMode) -> Color {
+    let [a, b, c, alpha] = v;
+    match mode {
+        BlendMode::Rgb => Color::new(a, b, c, alpha),
+        BlendMode::LinearRgb => Color::from_linear_rgba(a, b, c, alpha),
+        BlendMode::Lab => Color::from_laba(a, b, c, alpha),
+        _ => Color::from_oklaba(a, b, c, alpha),
+    }
+}
+
+// Fritsch-Carlson tangents, the curve never overshoots the neighbouring values
+fn tangents(pos: &[f32], values: &[f32]) -> Vec<f32> {
+    let n = values.len();
+    // A zero width segment is a hard stop, flat on both sides
+    let d: Vec<f32> = (0..n - 1)
+        .map(|i| {
+            let h = pos[i + 1] - pos[i];
+            if h > 0.0 {
+                (values[i + 1] - values[i]) / h
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let mut m = vec![0.0; n];
+    m[0] = d[0];
+    m[n - 1] = d[n - 2];
+    for i in 1..n - 1 {
+        m[i] = if d[i - 1] * d[i] <= 0.0 {
+            0.0
+        } else {
+            (d[i - 1] + d[i]) / 2.0
+        };
+    }
+
+    for i in 0..n - 1 {
+        if d[i] == 0.0 {
+            m[i] = 0.0;
+            m[i + 1] = 0.0;
+            continue;
+        }
+        let a = m[i] / d[i];
+        let b = m[i + 1] / d[i];
+        let s = a * a + b * b;
+        if s > 9.0 {
+            let tau = 3.0 / s.sqrt();
+            m[i] = tau * a * d[i];
+            m[i + 1] = tau * b * d[i];
+        }
+    }
+
+    m
+}
+
+// Monotone cubic interpolation of each channel in the blend mode color space
+#[derive(Clone)]
+pub struct MonotoneGradient {
+    values: Vec<[f32; 4]>,
+    tangents: Vec<[f32; 4]>,
+    pos: Vec<f32>,
+    mode: BlendMode,
+}
+
+impl MonotoneGradient {
+    pub fn new(colors: &[Color], pos: Option<&[f32]>, mode: BlendMode) -> Result<Self, String> {
+        if colors.is_empty() {
+            return Err("no colors".to_string());
+        }
+
+        let mut colors = colors.to_vec();
+        if colors.len() == 1 {
+            colors.push(colors[0].clone());
+        }
+
+        let pos = crate::util::stop_positions(colors.len(), pos)?;
+        let values: Vec<[f32; 4]> = colors.iter().map(|c| to_space(c, mode)).collect();
+
+        let mut tans = vec![[0.0; 4]; values.len()];
+        for k in 0..4 {
+            let channel: Vec<f32> = values.iter().map(|v| v[k]).collect();
+            for (t, m) in tans.iter_mut().zip(tangents(&pos, &channel)) {
+                t[k] = m;
+            }
+        }
+
+        Ok(Self {
+            values,
+            tangents: tans,
+            pos,
+            mode,
+        })
+    }
+}
+
+impl Gradient for MonotoneGradient {
+    fn at(&self, t: f32) -> Color {
+        let last = self.pos.len() - 1;
+
+        if t.is_nan() || t <= self.pos[0] {
+            return from_space(self.values[0], self.mode);
+        }
+
+        if t >= self.pos[last] {
+            return from_space(self.values[last], self.mode);
+        }
+
+        let i = self
+            .pos
+            .windows(2)
+            .position(|w| t <= w[1])
+            .unwrap_or(last - 1);
+        let h = self.pos[i + 1] - self.pos[i];
+        let s = (t - self.pos[i]) / h;
+
+        // Cubic Hermite basis
+        let h00 = 2.0 * s * s * s - 3.0 * s * s + 1.0;
+        let h10 = s * s * s - 2.0 * s * s + s;
+        let h01 = -2.0 * s * s * s + 3.0 * s * s;
+        let h11 = s * s * s - s * s;
+
+        let (y0, y1) = (self.values[i], self.values[i + 1]);
+        let (m0, m1) = (self.tangents[i], self.tangents[i + 1]);
+        let mut v = [0.0; 4];
+        for k in 0..4 {
+            v[k] = h00 * y0[k] + h10 * h * m0[k] + h01 * y1[k] + h11 * h * m1[k];
+        }
+
+        from_space(v, self.mode)
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.pos[0], self.pos[self.pos.len() - 1])
+    }
+}
+
+#[test]
+fn monotone_bounds() {
+    let colors: Vec<Color> = ["#000", "#f00", "#ff0", "#fff", "#00f"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let pos = [0.0, 0.1, 0.5, 0.6, 1.0];
+    let grad = MonotoneGradient::new(&colors, Some(&pos), BlendMode::Rgb).unwrap();
+
+    for (i, w) in pos.windows(2).enumerate() {
+        let (a, b) = (&colors[i], &colors[i + 1]);
+        for k in 0..=50 {
+            let t = w[0] + (w[1] - w[0]) * k as f32 / 50.0;
+            let c = grad.at(t);
+            for (v, lo, hi) in [(c.r, a.r, b.r), (c.g, a.g, b.g), (c.b, a.b, b.b)] {
+                assert!(v >= lo.min(hi) - 1e-5 && v <= lo.max(hi) + 1e-5);
+            }
+        }
+    }
+
+    assert_eq!(grad.at(0.5).to_hex_string(), "#ffff00");
+    assert_eq!(grad.domain(), (0.0, 1.0));
+}
+
+#[test]
+fn monotone_hard_stop() {
+    let colors: Vec<Color> = ["#000", "#fff", "#f00", "#00f"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let pos = [0.0, 0.5, 0.5, 1.0];
+    let grad = MonotoneGradient::new(&colors, Some(&pos), BlendMode::Rgb).unwrap();
+
+    for k in 0..=100 {
+        let c = grad.at(k as f32 / 100.0);
+        assert!([c.r, c.g, c.b, c.a].iter().all(|v| v.is_finite()));
+    }
+
+    // Stays within black .. white up to the stop
+    let c = grad.at(0.25);
+    assert!(c.r > 0.0 && c.r < 1.0 && c.r == c.g && c.g == c.b);
+    assert_eq!(grad.at(0.5).to_hex_string(), "#ffffff");
+    assert_eq!(grad.at(0.5001).to_hex_string(), "#ff0000");
+    assert_eq!(grad.at(1.0).to_hex_string(), "#0000ff");
+}
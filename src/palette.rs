@@ -0,0 +1,392 @@
+use colorgrad::Color;
+use std::fmt;
+use std::io::BufRead;
+
+#[derive(Debug)]
+pub struct Palette {
+    pub name: Option<String>,
+    pub colors: Vec<Color>,
+    pub pos: Option<Vec<f32>>,
+}
+
+#[derive(Debug)]
+pub struct PaletteError {
+    line: usize,
+    message: String,
+}
+
+impl PaletteError {
+    pub fn new(line: usize, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "Invalid palette (line {}): {}", self.line, self.message)
+        } else {
+            write!(f, "Invalid palette: {}", self.message)
+        }
+    }
+}
+
+// GIMP palette (gpl)
+pub fn parse_gpl<R: BufRead>(r: R) -> Result<Palette, PaletteError> {
+    let mut name = None;
+    let mut colors = Vec::new();
+
+    for (i, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| PaletteError::new(i + 1, &e.to_string()))?;
+        let line = line.trim();
+
+        if i == 0 {
+            if line != "GIMP Palette" {
+                return Err(PaletteError::new(1, "missing 'GIMP Palette' header"));
+            }
+            continue;
+        }
+
+        if let Some(s) = line.strip_prefix("Name:") {
+            name = Some(s.trim().to_string());
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+
+        let rgb: Vec<u8> = line
+            .split_whitespace()
+            .take(3)
+            .map(|s| s.parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| PaletteError::new(i + 1, "expected 'R G B [name]'"))?;
+
+        if rgb.len() != 3 {
+            return Err(PaletteError::new(i + 1, "expected 'R G B [name]'"));
+        }
+
+        colors.push(Color::from_rgba8(rgb[0], rgb[1], rgb[2], 255));
+    }
+
+    if colors.is_empty() {
+        return Err(PaletteError::new(0, "no colors"));
+    }
+
+    Ok(Palette {
+        name,
+        colors,
+        pos: None,
+    })
+}
+
+// Fractint palette (map), up to 256 lines of 'R G B [comment]'
+pub fn parse_map<R: BufRead>(r: R) -> Result<Palette, PaletteError> {
+    let mut colors = Vec::new();
+
+    for (i, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| PaletteError::new(i + 1, &e.to_string()))?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if colors.len() == 256 {
+            break;
+        }
+
+        let rgb: Vec<u8> = line
+            .split_whitespace()
+            .take(3)
+            .map(|s| s.parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| PaletteError::new(i + 1, "expected 'R G B' (0-255)"))?;
+
+        if rgb.len() != 3 {
+            return Err(PaletteError::new(i + 1, "expected 'R G B' (0-255)"));
+        }
+
+        colors.push(Color::from_rgba8(rgb[0], rgb[1], rgb[2], 255));
+    }
+
+    if colors.is_empty() {
+        return Err(PaletteError::new(0, "no colors"));
+    }
+
+    Ok(Palette {
+        name: None,
+        colors,
+        pos: None,
+    })
+}
+
+// Adobe Color Table (act), 256 RGB triplets with an optional 4 byte footer:
+// big-endian number of colors used and index of the transparent color (0xffff for none)
+pub fn parse_act(data: &[u8]) -> Result<Palette, PaletteError> {
+    let (count, transparent) = match data.len() {
+        768 => (256, None),
+        772 => {
+            let n = u16::from_be_bytes([data[768], data[769]]) as usize;
+            let t = u16::from_be_bytes([data[770], data[771]]) as usize;
+            (n, Some(t))
+        }
+        n => {
+            return Err(PaletteError::new(
+                0,
+                &format!("expected 768 or 772 bytes, file has {n}"),
+            ))
+        }
+    };
+
+    if count == 0 || count > 256 {
+        return Err(PaletteError::new(
+            0,
+            &format!("invalid color count {count}"),
+        ));
+    }
+
+    let colors = data[..count * 3]
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, c)| {
+            let a = if transparent == Some(i) { 0 } else { 255 };
+            Color::from_rgba8(c[0], c[1], c[2], a)
+        })
+        .collect();
+
+    Ok(Palette {
+        name: None,
+        colors,
+        pos: None,
+    })
+}
+
+// Split at commas outside parentheses, so rgb(0,0,0) stays in one piece
+fn split_colors(s: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    res.push(&s[start..]);
+    res
+}
+
+// Plain text list of CSS colors, one per line or comma separated.
+// Lines starting with '//', ';' or '# ' are comments.
+pub fn parse_text<R: BufRead>(r: R) -> Result<Palette, PaletteError> {
+    let mut colors = Vec::new();
+
+    for (i, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| PaletteError::new(i + 1, &e.to_string()))?;
+        let line = line.trim();
+
+        if line.is_empty()
+            || line == "#"
+            || line.starts_with("//")
+            || line.starts_with(';')
+            || line.starts_with("# ")
+        {
+            continue;
+        }
+
+        for s in split_colors(line) {
+            let s = s.trim();
+            if s.is_empty() {
+                continue;
+            }
+            let col = s
+                .parse::<Color>()
+                .map_err(|_| PaletteError::new(i + 1, &format!("invalid color '{s}'")))?;
+            colors.push(col);
+        }
+    }
+
+    if colors.is_empty() {
+        return Err(PaletteError::new(0, "no colors"));
+    }
+
+    Ok(Palette {
+        name: None,
+        colors,
+        pos: None,
+    })
+}
+
+#[test]
+fn text_palette() {
+    let pal =
+        parse_text("# comment\n\n#ff0000\nrgb(0, 255, 0), blue\n// another\n".as_bytes()).unwrap();
+    let hex: Vec<_> = pal.colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#ff0000", "#00ff00", "#0000ff"]);
+
+    let err = parse_text("red\nnot-a-color\n".as_bytes()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid palette (line 2): invalid color 'not-a-color'"
+    );
+}
+
+#[test]
+fn map_palette() {
+    let pal =
+        parse_map("; comment\n\n255 0 0 red\n# another\n0 255 0\n  0 0 255\n".as_bytes()).unwrap();
+    let hex: Vec<_> = pal.colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#ff0000", "#00ff00", "#0000ff"]);
+
+    // Lines past 256 colors are ignored
+    let mut s = "1 2 3\n".repeat(256);
+    s.push_str("4 5 6\nnot a color\n");
+    let pal = parse_map(s.as_bytes()).unwrap();
+    assert_eq!(pal.colors.len(), 256);
+    assert_eq!(pal.colors[255].to_hex_string(), "#010203");
+
+    let err = parse_map("1 2 3\n12 abc 3\n".as_bytes()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid palette (line 2): expected 'R G B' (0-255)"
+    );
+    assert!(parse_map("1 2 256\n".as_bytes()).is_err());
+    assert!(parse_map("1 2\n".as_bytes()).is_err());
+    assert!(parse_map("; empty\n".as_bytes()).is_err());
+}
+
+// JSON array of colors, either ["#f00", ..] or [{"color": "#f00", "position": 0.2}, ..].
+// The "hex" key written by --json is accepted as well.
+pub fn parse_json(s: &str) -> Result<Palette, PaletteError> {
+    let value: serde_json::Value =
+        serde_json::from_str(s).map_err(|e| PaletteError::new(e.line(), &e.to_string()))?;
+
+    let items = value
+        .as_array()
+        .ok_or_else(|| PaletteError::new(0, "expected an array of colors"))?;
+
+    let mut colors = Vec::with_capacity(items.len());
+    let mut pos = Vec::with_capacity(items.len());
+
+    for (i, item) in items.iter().enumerate() {
+        let (col, p) = match item {
+            serde_json::Value::String(s) => (s.as_str(), None),
+            serde_json::Value::Object(obj) => {
+                let col = obj
+                    .get("color")
+                    .or_else(|| obj.get("hex"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| PaletteError::new(0, &format!("item {i}: missing \"color\"")))?;
+                let p = match obj.get("position") {
+                    Some(v) => Some(v.as_f64().ok_or_else(|| {
+                        PaletteError::new(0, &format!("item {i}: invalid position"))
+                    })? as f32),
+                    None => None,
+                };
+                (col, p)
+            }
+            _ => {
+                return Err(PaletteError::new(
+                    0,
+                    &format!("item {i}: expected a string or an object"),
+                ))
+            }
+        };
+
+        let col = col
+            .parse::<Color>()
+            .map_err(|_| PaletteError::new(0, &format!("item {i}: invalid color '{col}'")))?;
+        colors.push(col);
+
+        if let Some(p) = p {
+            if let Some(&prev) = pos.last() {
+                if p < prev {
+                    return Err(PaletteError::new(
+                        0,
+                        &format!("item {i}: positions must be in ascending order"),
+                    ));
+                }
+            }
+            pos.push(p);
+        }
+    }
+
+    if colors.is_empty() {
+        return Err(PaletteError::new(0, "no colors"));
+    }
+
+    let pos = if pos.is_empty() {
+        None
+    } else if pos.len() == colors.len() {
+        Some(pos)
+    } else {
+        return Err(PaletteError::new(
+            0,
+            "position must be set for all colors or none",
+        ));
+    };
+
+    Ok(Palette {
+        name: None,
+        colors,
+        pos,
+    })
+}
+
+#[test]
+fn json_palette() {
+    let pal = parse_json(r##"["#ff0000", "lime"]"##).unwrap();
+    assert_eq!(pal.colors.len(), 2);
+    assert!(pal.pos.is_none());
+
+    let pal = parse_json(
+        r##"[{"color":"#f00","position":0.2},{"hex":"#0000ff","rgb":[0,0,255],"position":1}]"##,
+    )
+    .unwrap();
+    assert_eq!(pal.colors[1].to_hex_string(), "#0000ff");
+    assert_eq!(pal.pos, Some(vec![0.2, 1.0]));
+
+    let err = parse_json(r##"[{"color":"red","position":0.5},{"color":"blue","position":0.1}]"##)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid palette: item 1: positions must be in ascending order"
+    );
+
+    assert!(parse_json(r##"[{"color":"red","position":0},"blue"]"##).is_err());
+    assert!(parse_json("[]").is_err());
+}
+
+#[test]
+fn act_palette() {
+    let mut data = vec![0u8; 768];
+    data[..6].copy_from_slice(&[255, 0, 0, 0, 0, 255]);
+    let pal = parse_act(&data).unwrap();
+    assert_eq!(pal.colors.len(), 256);
+    assert_eq!(pal.colors[1].to_hex_string(), "#0000ff");
+
+    // Footer: 3 colors used, the last one transparent
+    data.extend_from_slice(&[0, 3, 0, 2]);
+    let pal = parse_act(&data).unwrap();
+    let hex: Vec<_> = pal.colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#ff0000", "#0000ff", "#00000000"]);
+
+    data[770..].copy_from_slice(&[0xff, 0xff]);
+    assert_eq!(parse_act(&data).unwrap().colors[2].a, 1.0);
+
+    data[768..770].copy_from_slice(&[0, 0]);
+    assert!(parse_act(&data).is_err());
+    assert!(parse_act(&data[..700]).is_err());
+}
@@ -0,0 +1,277 @@
+use colorgrad::{Color, Gradient};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Space {
+    Oklch,
+    Hsluv,
+    Hsl,
+    Hsv,
+}
+
+// [hue (degrees, NaN if achromatic), chroma, lightness, alpha]
+fn to_polar(col: &Color, space: Space) -> [f32; 4] {
+    let [h, c, l] = match space {
+        Space::Oklch => {
+            let [l, a, b, _] = col.to_oklaba();
+            let c = (a * a + b * b).sqrt();
+            [b.atan2(a).to_degrees(), c, l]
+        }
+        Space::Hsluv => {
+            let [h, s, l] = rgb_to_hsluv(col);
+            [h, s, l]
+        }
+        Space::Hsl => {
+            let [h, s, l, _] = col.to_hsla();
+            [h, s, l]
+        }
+        Space::Hsv => {
+            let [h, s, v, _] = col.to_hsva();
+            [h, s, v]
+        }
+    };
+    let h = if c < 1e-4 { f32::NAN } else { h };
+    [h, c, l, col.a]
+}
+
+fn from_polar(v: [f32; 4], space: Space) -> Color {
+    let [h, c, l, alpha] = v;
+    let h = if h.is_nan() { 0.0 } else { h };
+    match space {
+        Space::Oklch => {
+            let h = h.to_radians();
+            Color::from_oklaba(l, c * h.cos(), c * h.sin(), alpha)
+        }
+        Space::Hsluv => hsluv_to_rgb(h, c, l, alpha),
+        Space::Hsl => Color::from_hsla(h.rem_euclid(360.0), c, l, alpha),
+        Space::Hsv => Color::from_hsva(h.rem_euclid(360.0), c, l, alpha),
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// Shortest arc, a missing (achromatic) hue takes the other one
+fn lerp_hue(a: f32, b: f32, t: f32) -> f32 {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => f32::NAN,
+        (true, false) => b,
+        (false, true) => a,
+        _ => {
+            let delta = ((b - a) % 360.0 + 540.0) % 360.0 - 180.0;
+            a + t * delta
+        }
+    }
+}
+
+// Linear interpolation in a cylindrical color space
+#[derive(Clone)]
+pub struct PolarGradient {
+    stops: Vec<[f32; 4]>,
+    pos: Vec<f32>,
+    space: Space,
+}
+
+impl PolarGradient {
+    // Positions follow GradientBuilder::domain(): none (0..1), [min, max] or one per color
+    pub fn new(colors: &[Color], pos: Option<&[f32]>, space: Space) -> Result<Self, String> {
+        if colors.is_empty() {
+            return Err("no colors".to_string());
+        }
+
+        let mut colors = colors.to_vec();
+        if colors.len() == 1 {
+            colors.push(colors[0].clone());
+        }
+
+        let n = colors.len();
+
+        let pos = crate::util::stop_positions(n, pos)?;
+
+        Ok(Self {
+            stops: colors.iter().map(|c| to_polar(c, space)).collect(),
+            pos,
+            space,
+        })
+    }
+}
+
+impl Gradient for PolarGradient {
+    fn at(&self, t: f32) -> Color {
+        let last = self.pos.len() - 1;
+
+        if t.is_nan() || t <= self.pos[0] {
+            return from_polar(self.stops[0], self.space);
+        }
+
+        if t >= self.pos[last] {
+            return from_polar(self.stops[last], self.space);
+        }
+
+        let i = self
+            .pos
+            .windows(2)
+            .position(|w| t <= w[1])
+            .unwrap_or(last - 1);
+        let u = (t - self.pos[i]) / (self.pos[i + 1] - self.pos[i]);
+        let [h0, c0, l0, a0] = self.stops[i];
+        let [h1, c1, l1, a1] = self.stops[i + 1];
+
+        from_polar(
+            [
+                lerp_hue(h0, h1, u),
+                lerp(c0, c1, u),
+                lerp(l0, l1, u),
+                lerp(a0, a1, u),
+            ],
+            self.space,
+        )
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.pos[0], self.pos[self.pos.len() - 1])
+    }
+}
+
+// HSLuv <https://www.hsluv.org/>
+
+const M: [[f32; 3]; 3] = [
+    [3.240_97, -1.537_383_2, -0.498_610_76],
+    [-0.969_243_6, 1.875_967_5, 0.041_555_06],
+    [0.055_630_08, -0.203_976_96, 1.056_971_5],
+];
+const M_INV: [[f32; 3]; 3] = [
+    [0.412_390_8, 0.357_584_33, 0.180_480_8],
+    [0.212_639, 0.715_168_7, 0.072_192_32],
+    [0.019_330_818, 0.119_194_78, 0.950_532_14],
+];
+const REF_U: f32 = 0.197_83;
+const REF_V: f32 = 0.468_319_98;
+const KAPPA: f32 = 903.296_3;
+const EPSILON: f32 = 0.008_856_452;
+
+fn max_chroma(l: f32, h: f32) -> f32 {
+    let hrad = h.to_radians();
+    let sub1 = (l + 16.0).powi(3) / 1_560_896.0;
+    let sub2 = if sub1 > EPSILON { sub1 } else { l / KAPPA };
+    let mut min = f32::MAX;
+
+    for [m1, m2, m3] in M {
+        for t in [0.0, 1.0] {
+            let top1 = (284_517.0 * m1 - 94_839.0 * m3) * sub2;
+            let top2 =
+                (838_422.0 * m3 + 769_860.0 * m2 + 731_718.0 * m1) * l * sub2 - 769_860.0 * t * l;
+            let bottom = (632_260.0 * m3 - 126_452.0 * m2) * sub2 + 126_452.0 * t;
+            let (slope, intercept) = (top1 / bottom, top2 / bottom);
+            let len = intercept / (hrad.sin() - slope * hrad.cos());
+            if len >= 0.0 {
+                min = min.min(len);
+            }
+        }
+    }
+
+    min
+}
+
+// [h, s (0..100), l (0..100)]
+fn rgb_to_hsluv(col: &Color) -> [f32; 3] {
+    let [r, g, b, _] = col.to_linear_rgba();
+    let [x, y, z] = M_INV.map(|m| m[0] * r + m[1] * g + m[2] * b);
+
+    let l = if y <= EPSILON {
+        y * KAPPA
+    } else {
+        116.0 * y.cbrt() - 16.0
+    };
+
+    if l < 1e-8 {
+        return [0.0, 0.0, 0.0];
+    }
+
+    let d = x + 15.0 * y + 3.0 * z;
+    let u = 13.0 * l * (4.0 * x / d - REF_U);
+    let v = 13.0 * l * (9.0 * y / d - REF_V);
+    let c = (u * u + v * v).sqrt();
+    let h = (v.atan2(u).to_degrees() + 360.0) % 360.0;
+
+    if l > 99.999_99 {
+        return [h, 0.0, 100.0];
+    }
+
+    [h, c / max_chroma(l, h) * 100.0, l]
+}
+
+fn hsluv_to_rgb(h: f32, s: f32, l: f32, alpha: f32) -> Color {
+    if l > 99.999_99 {
+        return Color::new(1.0, 1.0, 1.0, alpha);
+    }
+
+    if l < 1e-8 {
+        return Color::new(0.0, 0.0, 0.0, alpha);
+    }
+
+    let c = max_chroma(l, h) * s / 100.0;
+    let hrad = h.to_radians();
+    let (u, v) = (c * hrad.cos(), c * hrad.sin());
+
+    let var_u = u / (13.0 * l) + REF_U;
+    let var_v = v / (13.0 * l) + REF_V;
+    let y = if l <= 8.0 {
+        l / KAPPA
+    } else {
+        ((l + 16.0) / 116.0).powi(3)
+    };
+    let x = -(9.0 * y * var_u) / ((var_u - 4.0) * var_v - var_u * var_v);
+    let z = (9.0 * y - 15.0 * var_v * y - var_v * x) / (3.0 * var_v);
+
+    let [r, g, b] = M.map(|m| m[0] * x + m[1] * y + m[2] * z);
+    Color::from_linear_rgba(
+        r.clamp(0.0, 1.0),
+        g.clamp(0.0, 1.0),
+        b.clamp(0.0, 1.0),
+        alpha,
+    )
+}
+
+#[test]
+fn hsluv_round_trip() {
+    for s in ["#ff0000", "#00ff00", "#3366cc", "#808080", "#fedcba"] {
+        let c = Color::from_html(s).unwrap();
+        let [h, s, l] = rgb_to_hsluv(&c);
+        assert_eq!(hsluv_to_rgb(h, s, l, 1.0).to_rgba8(), c.to_rgba8());
+    }
+}
+
+#[test]
+fn polar_midpoint() {
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(0.0, 1.0, 0.0, 1.0),
+    ];
+
+    for space in [Space::Oklch, Space::Hsluv, Space::Hsl, Space::Hsv] {
+        let grad = PolarGradient::new(&colors, None, space).unwrap();
+        let [_, s, _, _] = grad.at(0.5).clamp().to_hsla();
+        assert!(s > 0.9);
+    }
+}
+
+#[test]
+fn hsl_shortest_hue() {
+    // red (0) -> magenta (300) goes backwards through 330, not through green
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(1.0, 0.0, 1.0, 1.0),
+    ];
+
+    for space in [Space::Hsl, Space::Hsv] {
+        let grad = PolarGradient::new(&colors, None, space).unwrap();
+        for i in 0..=20 {
+            let c = grad.at(i as f32 / 20.0);
+            assert!(c.g < 0.01);
+            let h = c.to_hsla()[0];
+            assert!(h == 0.0 || h >= 299.0);
+        }
+        assert_eq!(grad.at(0.5).to_hex_string(), "#ff0080");
+    }
+}
@@ -0,0 +1,121 @@
+use image::RgbaImage;
+use std::collections::HashMap;
+
+// Sixel images can use at most 256 color registers. Low bits are dropped from each
+// channel until the image fits, which is enough for the smooth colors of a gradient.
+fn quantize(img: &RgbaImage) -> (Vec<[u8; 3]>, Vec<usize>) {
+    for shift in 0..8 {
+        let mask = 0xff << shift;
+        let mut palette = Vec::new();
+        let mut index = HashMap::new();
+        let mut pixels = Vec::with_capacity((img.width() * img.height()) as usize);
+
+        for px in img.pixels() {
+            let c = [px[0] & mask, px[1] & mask, px[2] & mask];
+            let i = *index.entry(c).or_insert_with(|| {
+                palette.push(c);
+                palette.len() - 1
+            });
+            if palette.len() > 256 {
+                break;
+            }
+            pixels.push(i);
+        }
+
+        if palette.len() <= 256 {
+            return (palette, pixels);
+        }
+    }
+
+    unreachable!()
+}
+
+// Encode an opaque image as a sixel escape sequence, alpha is ignored
+pub fn encode(img: &RgbaImage) -> String {
+    let (w, h) = (img.width() as usize, img.height() as usize);
+    let (palette, pixels) = quantize(img);
+
+    let mut s = format!("\x1BP0;1;0q\"1;1;{w};{h}");
+
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let pct = |v: u8| (v as u32 * 100 + 127) / 255;
+        s.push_str(&format!("#{i};2;{};{};{}", pct(*r), pct(*g), pct(*b)));
+    }
+
+    for band in (0..h).step_by(6) {
+        let rows = (h - band).min(6);
+        let mut first = true;
+
+        for color in 0..palette.len() {
+            // Sixel bits of this color for each column, 0 if unused in this band
+            let bits: Vec<u8> = (0..w)
+                .map(|x| {
+                    (0..rows)
+                        .filter(|dy| pixels[(band + dy) * w + x] == color)
+                        .fold(0, |acc, dy| acc | (1 << dy))
+                })
+                .collect();
+
+            if bits.iter().all(|&b| b == 0) {
+                continue;
+            }
+
+            if !first {
+                s.push('$');
+            }
+            first = false;
+            s.push_str(&format!("#{color}"));
+
+            // Run-length encoded
+            let mut x = 0;
+            while x < w {
+                let mut n = 1;
+                while x + n < w && bits[x + n] == bits[x] {
+                    n += 1;
+                }
+                let ch = (63 + bits[x]) as char;
+                if n > 3 {
+                    s.push_str(&format!("!{n}{ch}"));
+                } else {
+                    (0..n).for_each(|_| s.push(ch));
+                }
+                x += n;
+            }
+        }
+
+        s.push('-');
+    }
+
+    s.push_str("\x1B\\");
+    s
+}
+
+#[test]
+fn sixel_encoding() {
+    use image::Rgba;
+
+    // Left half red, right half blue, 8 rows (two bands)
+    let img = RgbaImage::from_fn(10, 8, |x, _| {
+        if x < 5 {
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 255, 255])
+        }
+    });
+    let s = encode(&img);
+
+    assert!(s.starts_with("\x1BP0;1;0q\"1;1;10;8#0;2;100;0;0#1;2;0;0;100"));
+    assert!(s.ends_with("\x1B\\"));
+    // Full band: 6 rows set is '~', 5 columns each
+    assert!(s.contains("#0!5~!5?$#1!5?!5~-"));
+    // Last band has 2 rows
+    assert!(s.contains("#0!5B!5?$#1!5?!5B-"));
+
+    // Too many colors for the palette
+    let img = RgbaImage::from_fn(1000, 1, |x, _| {
+        Rgba([(x % 256) as u8, (x / 4) as u8, 7, 255])
+    });
+    let (palette, pixels) = quantize(&img);
+    assert!(palette.len() <= 256);
+    assert_eq!(pixels.len(), 1000);
+}
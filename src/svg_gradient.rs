@@ -1,4 +1,4 @@
-use colorgrad::{Color, GradientBuilder, LinearGradient};
+use colorgrad::Color;
 use svg::node::element::tag as svg_tag;
 use svg::parser::Event;
 
@@ -36,40 +36,149 @@ fn parse_styles(s: &str) -> (Option<&str>, Option<&str>) {
     val
 }
 
+// spreadMethod, how the gradient continues outside its stops
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) enum SpreadMethod {
+    #[default]
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+impl SpreadMethod {
+    fn new(attributes: &svg::node::Attributes) -> Self {
+        match attributes.get("spreadMethod").map(|v| v.trim()) {
+            Some("reflect") => Self::Reflect,
+            Some("repeat") => Self::Repeat,
+            _ => Self::Pad,
+        }
+    }
+}
+
+// Raw radialGradient geometry attributes, only reported since the gradient is used as 1D
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct RadialGeometry {
+    pub(crate) cx: Option<String>,
+    pub(crate) cy: Option<String>,
+    pub(crate) r: Option<String>,
+    pub(crate) fx: Option<String>,
+    pub(crate) fy: Option<String>,
+    pub(crate) fr: Option<String>,
+}
+
+impl RadialGeometry {
+    fn new(attributes: &svg::node::Attributes) -> Self {
+        let get = |name| attributes.get(name).map(|v| v.to_string());
+        Self {
+            cx: get("cx"),
+            cy: get("cy"),
+            r: get("r"),
+            fx: get("fx"),
+            fy: get("fy"),
+            fr: get("fr"),
+        }
+    }
+
+    // "cx=50% cy=50% r=40%" of the attributes present
+    pub(crate) fn describe(&self) -> String {
+        [
+            ("cx", &self.cx),
+            ("cy", &self.cy),
+            ("r", &self.r),
+            ("fx", &self.fx),
+            ("fy", &self.fy),
+            ("fr", &self.fr),
+        ]
+        .iter()
+        .filter_map(|(name, v)| v.as_ref().map(|v| format!("{name}={v}")))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
 #[derive(Debug)]
-struct SvgGradient {
-    id: Option<String>,
-    colors: Vec<Color>,
-    pos: Vec<f32>,
+pub(crate) struct SvgGradient {
+    pub(crate) id: Option<String>,
+    pub(crate) colors: Vec<Color>,
+    pub(crate) pos: Vec<f32>,
+    // Raw gradientTransform attribute, not applied
+    pub(crate) transform: Option<String>,
+    pub(crate) spread: SpreadMethod,
+    // Set for radialGradient
+    pub(crate) radial: Option<RadialGeometry>,
+    // Reason the gradient can't be used
+    pub(crate) error: Option<String>,
+    href: Option<String>,
+}
+
+fn new_gradient(attributes: &svg::node::Attributes, radial: bool) -> SvgGradient {
+    SvgGradient {
+        id: attributes.get("id").map(|v| v.to_string()),
+        colors: Vec::new(),
+        pos: Vec::new(),
+        transform: attributes.get("gradientTransform").map(|v| v.to_string()),
+        spread: SpreadMethod::new(attributes),
+        radial: radial.then(|| RadialGeometry::new(attributes)),
+        error: None,
+        href: attributes
+            .get("href")
+            .or_else(|| attributes.get("xlink:href"))
+            .map(|v| v.to_string()),
+    }
+}
+
+pub(crate) struct SvgOptions {
+    // A stop offset less than the previous one makes the gradient invalid
+    // instead of being clamped.
+    pub(crate) strict: bool,
+    // Used for stop-color currentColor / inherit
+    pub(crate) current_color: Color,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            current_color: Color::new(0.0, 0.0, 0.0, 1.0),
+        }
+    }
 }
 
-pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
+pub(crate) fn parse_svg(path: &str, opt: &SvgOptions) -> Vec<SvgGradient> {
+    let mut content = String::new();
+    svg::open(path, &mut content).unwrap();
+    parse_svg_str(&content, opt)
+}
+
+fn parse_svg_str(content: &str, opt: &SvgOptions) -> Vec<SvgGradient> {
     let mut res = Vec::new();
     let mut index = 0;
     let mut prev_pos = f32::NEG_INFINITY;
-    let mut content = String::new();
 
-    for event in svg::open(path, &mut content).unwrap() {
+    for event in svg::read(content).unwrap() {
         match event {
-            Event::Tag(svg_tag::LinearGradient, t, attributes)
-            | Event::Tag(svg_tag::RadialGradient, t, attributes) => match t {
-                svg_tag::Type::Start => {
-                    let id = attributes.get("id").map(|v| v.to_string());
-
-                    res.push(SvgGradient {
-                        id,
-                        colors: Vec::new(),
-                        pos: Vec::new(),
-                    });
-                }
-                svg_tag::Type::End => {
-                    index += 1;
-                    prev_pos = f32::NEG_INFINITY;
+            Event::Tag(
+                tag @ (svg_tag::LinearGradient | svg_tag::RadialGradient),
+                t,
+                attributes,
+            ) => {
+                let radial = tag == svg_tag::RadialGradient;
+                match t {
+                    svg_tag::Type::Start => {
+                        res.push(new_gradient(&attributes, radial));
+                    }
+                    svg_tag::Type::End => {
+                        index += 1;
+                        prev_pos = f32::NEG_INFINITY;
+                    }
+                    svg_tag::Type::Empty => {
+                        res.push(new_gradient(&attributes, radial));
+                        index += 1;
+                    }
                 }
-                svg_tag::Type::Empty => {}
-            },
+            }
             Event::Tag(svg_tag::Stop, _, attributes) => {
-                if res.is_empty() {
+                if index >= res.len() {
                     continue;
                 }
 
@@ -97,7 +206,12 @@ pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
                 }
 
                 let color = if let Some(col) = color {
-                    if let Ok(c) = col.parse::<Color>() {
+                    let col = col.trim();
+                    if col.eq_ignore_ascii_case("currentcolor")
+                        || col.eq_ignore_ascii_case("inherit")
+                    {
+                        Some(opt.current_color.clone())
+                    } else if let Ok(c) = col.parse::<Color>() {
                         Some(c)
                     } else {
                         None
@@ -112,8 +226,9 @@ pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
                     None
                 };
 
+                // Offsets are clamped to [0, 1] as in the SVG spec
                 let offset = if let Some(pos) = attributes.get("offset") {
-                    parse_percent_or_float(pos)
+                    parse_percent_or_float(pos).map(|t| t.clamp(0.0, 1.0))
                 } else {
                     None
                 };
@@ -128,6 +243,12 @@ pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
                     color
                 };
 
+                if opt.strict && offset < prev_pos && res[index].error.is_none() {
+                    res[index].error = Some(format!(
+                        "stop offset {offset} is less than the previous offset {prev_pos}"
+                    ));
+                }
+
                 prev_pos = if offset.is_finite() {
                     offset.max(prev_pos)
                 } else {
@@ -141,9 +262,16 @@ pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
         }
     }
 
+    resolve_href(&mut res);
+
     let mut gradients = Vec::new();
 
     for mut g in res {
+        if g.error.is_some() {
+            gradients.push(g);
+            continue;
+        }
+
         if g.colors.is_empty() {
             continue;
         }
@@ -158,16 +286,230 @@ pub(crate) fn parse_svg(path: &str) -> Vec<(LinearGradient, Option<String>)> {
             g.colors.push(g.colors.last().unwrap().clone());
         }
 
-        let grad = GradientBuilder::new()
-            .colors(&g.colors)
-            .domain(&g.pos)
-            .build::<LinearGradient>();
+        gradients.push(g);
+    }
+
+    gradients
+}
+
+// Gradients without stops inherit the stops of the gradient referenced by
+// href / xlink:href, following chains.
+fn resolve_href(res: &mut [SvgGradient]) {
+    for i in 0..res.len() {
+        if !res[i].colors.is_empty() {
+            continue;
+        }
+
+        let mut href = res[i].href.clone();
+        let mut depth = 0;
+
+        while let Some(h) = href {
+            depth += 1;
+
+            if depth > res.len() {
+                res[i].error = Some(format!("circular reference '{h}'"));
+                break;
+            }
+
+            let target = h.trim_start_matches('#');
 
-        match grad {
-            Ok(grad) => gradients.push((grad, g.id)),
-            Err(e) => eprintln!("{e}"),
+            match res.iter().position(|g| g.id.as_deref() == Some(target)) {
+                Some(j) if !res[j].colors.is_empty() => {
+                    res[i].colors = res[j].colors.clone();
+                    res[i].pos = res[j].pos.clone();
+                    break;
+                }
+                Some(j) => href = res[j].href.clone(),
+                None => {
+                    res[i].error = Some(format!("referenced gradient '{h}' not found"));
+                    break;
+                }
+            }
         }
     }
+}
+
+#[test]
+fn svg_round_trip() {
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(0.0, 1.0, 0.5, 0.5),
+        Color::new(0.2, 0.4, 1.0, 1.0),
+    ];
+    let offsets = [0.0, 0.25, 1.0];
+
+    let path = std::env::temp_dir().join("gradient-test-round-trip.svg");
+    std::fs::write(&path, crate::util::to_svg(&colors, &offsets, 90.0)).unwrap();
+    let res = parse_svg(path.to_str().unwrap(), &SvgOptions::default());
+
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].id.as_deref(), Some("gradient"));
+    assert_eq!(res[0].pos, offsets);
+    for (a, b) in res[0].colors.iter().zip(&colors) {
+        assert_eq!(a.to_rgba8(), b.to_rgba8());
+    }
+}
 
-    gradients
+#[test]
+fn gradient_transform() {
+    let res = parse_svg_str(
+        r##"<svg>
+        <linearGradient id="a" gradientTransform="rotate(90)">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="b">
+            <stop offset="0" stop-color="red"/>
+        </linearGradient>
+        </svg>"##,
+        &SvgOptions::default(),
+    );
+
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].transform.as_deref(), Some("rotate(90)"));
+    assert_eq!(res[1].transform, None);
+}
+
+#[test]
+fn href_inheritance() {
+    let res = parse_svg_str(
+        r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink">
+        <linearGradient id="a">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="b" xlink:href="#a"/>
+        <radialGradient id="c" href="#b"></radialGradient>
+        <linearGradient id="d" xlink:href="#missing"/>
+        </svg>"##,
+        &SvgOptions::default(),
+    );
+
+    assert_eq!(res.len(), 4);
+    for g in &res[1..3] {
+        assert_eq!(g.colors, res[0].colors);
+        assert_eq!(g.pos, res[0].pos);
+        assert!(g.error.is_none());
+    }
+    assert_eq!(res[3].id.as_deref(), Some("d"));
+    assert!(res[3].error.is_some());
+}
+
+#[test]
+fn strict_offsets() {
+    let svg = r##"<svg>
+        <linearGradient id="a">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="0.6" stop-color="lime"/>
+            <stop offset="0.4" stop-color="blue"/>
+        </linearGradient>
+        </svg>"##;
+
+    let res = parse_svg_str(svg, &SvgOptions::default());
+    assert!(res[0].error.is_none());
+    assert_eq!(res[0].pos, [0.0, 0.6, 0.6, 1.0]);
+
+    let opt = SvgOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let res = parse_svg_str(svg, &opt);
+    assert!(res[0].error.is_some());
+}
+
+#[test]
+fn offset_clamping() {
+    let res = parse_svg_str(
+        r##"<svg>
+        <linearGradient id="a">
+            <stop offset="-10%" stop-color="red"/>
+            <stop offset="0.5" stop-color="lime"/>
+            <stop offset="150%" stop-color="blue"/>
+        </linearGradient>
+        </svg>"##,
+        &SvgOptions::default(),
+    );
+
+    assert_eq!(res[0].pos, [0.0, 0.5, 1.0]);
+    assert_eq!(res[0].colors.len(), 3);
+}
+
+#[test]
+fn current_color() {
+    let svg = r##"<svg>
+        <linearGradient id="a">
+            <stop offset="0" stop-color="currentColor"/>
+            <stop offset="1" style="stop-color: inherit"/>
+        </linearGradient>
+        </svg>"##;
+
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    let res = parse_svg_str(svg, &SvgOptions::default());
+    assert_eq!(res[0].colors, [black.clone(), black]);
+
+    let gold = Color::from_html("gold").unwrap();
+    let opt = SvgOptions {
+        current_color: gold.clone(),
+        ..Default::default()
+    };
+    let res = parse_svg_str(svg, &opt);
+    assert_eq!(res[0].colors, [gold.clone(), gold]);
+}
+
+#[test]
+fn radial_geometry() {
+    let svg = r##"<svg>
+        <radialGradient id="a" cx="50%" cy="40%" r="0.5" fx="30%" fy="20%" fr="5%">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </radialGradient>
+        <radialGradient id="b" r="10">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </radialGradient>
+        <linearGradient id="c" x1="0">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        </svg>"##;
+    let res = parse_svg_str(svg, &SvgOptions::default());
+
+    let geo = res[0].radial.as_ref().unwrap();
+    assert_eq!(geo.cx.as_deref(), Some("50%"));
+    assert_eq!(geo.fr.as_deref(), Some("5%"));
+    assert_eq!(geo.describe(), "cx=50% cy=40% r=0.5 fx=30% fy=20% fr=5%");
+
+    let geo = res[1].radial.as_ref().unwrap();
+    assert_eq!(geo.describe(), "r=10");
+    assert!(res[2].radial.is_none());
+
+    // Stops are the same for all three
+    for g in &res[1..] {
+        assert_eq!(g.colors, res[0].colors);
+        assert_eq!(g.pos, res[0].pos);
+    }
+}
+
+#[test]
+fn spread_method() {
+    let res = parse_svg_str(
+        r##"<svg>
+        <linearGradient id="a" spreadMethod="reflect"><stop offset="0" stop-color="red"/></linearGradient>
+        <radialGradient id="b" spreadMethod="repeat"><stop offset="0" stop-color="red"/></radialGradient>
+        <linearGradient id="c" spreadMethod="pad"><stop offset="0" stop-color="red"/></linearGradient>
+        <linearGradient id="d"><stop offset="0" stop-color="red"/></linearGradient>
+        </svg>"##,
+        &SvgOptions::default(),
+    );
+
+    let spread: Vec<_> = res.iter().map(|g| g.spread).collect();
+    assert_eq!(
+        spread,
+        [
+            SpreadMethod::Reflect,
+            SpreadMethod::Repeat,
+            SpreadMethod::Pad,
+            SpreadMethod::Pad
+        ]
+    );
 }
@@ -1,4 +1,8 @@
-use crate::{Color, OutputColor};
+use crate::{
+    BarChar, BlendMode, Color, Cvd, Dither, Gradient, HexAlpha, Interpolation, OutputColor,
+    PackedOrder, PlotSpace, SortBy, SpreadMethod,
+};
+use image::{Rgba, RgbaImage};
 
 pub fn blend_color(fg: &Color, bg: &Color) -> Color {
     Color::new(
@@ -9,6 +13,20 @@ pub fn blend_color(fg: &Color, bg: &Color) -> Color {
     )
 }
 
+// Raise each channel to 1/gamma
+pub fn apply_gamma(col: &Color, gamma: f32) -> Color {
+    if gamma == 1.0 {
+        return col.clone();
+    }
+    let g = 1.0 / gamma;
+    Color::new(
+        col.r.max(0.0).powf(g),
+        col.g.max(0.0).powf(g),
+        col.b.max(0.0).powf(g),
+        col.a,
+    )
+}
+
 pub fn blend_on(fg: &mut Color, bg: &Color) {
     fg.r = ((1.0 - fg.a) * bg.r) + (fg.a * fg.r);
     fg.g = ((1.0 - fg.a) * bg.g) + (fg.a * fg.g);
@@ -16,84 +34,2048 @@ pub fn blend_on(fg: &mut Color, bg: &Color) {
     fg.a = 1.0;
 }
 
-pub fn fmt_color(col: &Color, cb: &[Color; 2], width: usize) -> String {
+// SGR parameters for foreground (38) or background (48) color
+pub fn sgr_color(code: u8, col: &Color, truecolor: bool) -> String {
+    if truecolor {
+        let [r, g, b, _] = col.to_rgba8();
+        format!("{code};2;{r};{g};{b}")
+    } else {
+        format!("{code};5;{}", xterm256(col))
+    }
+}
+
+// Nearest xterm 256 color palette index (color cube or grayscale ramp)
+pub fn xterm256(col: &Color) -> u8 {
+    const LEVELS: [i32; 6] = [0, 95, 135, 175, 215, 255];
+
+    fn nearest_level(v: i32) -> usize {
+        let mut idx = 0;
+        for (i, l) in LEVELS.iter().enumerate() {
+            if (v - l).abs() < (v - LEVELS[idx]).abs() {
+                idx = i;
+            }
+        }
+        idx
+    }
+
+    fn dist(a: [i32; 3], b: [i32; 3]) -> i32 {
+        (a[0] - b[0]).pow(2) + (a[1] - b[1]).pow(2) + (a[2] - b[2]).pow(2)
+    }
+
+    let [r, g, b, _] = col.to_rgba8();
+    let rgb = [r as i32, g as i32, b as i32];
+
+    let (ri, gi, bi) = (
+        nearest_level(rgb[0]),
+        nearest_level(rgb[1]),
+        nearest_level(rgb[2]),
+    );
+    let cube = [LEVELS[ri], LEVELS[gi], LEVELS[bi]];
+
+    let avg = (rgb[0] + rgb[1] + rgb[2]) / 3;
+    let gray = ((avg - 8 + 5) / 10).clamp(0, 23);
+    let gv = 8 + gray * 10;
+
+    if dist(rgb, [gv, gv, gv]) < dist(rgb, cube) {
+        232 + gray as u8
+    } else {
+        16 + (36 * ri + 6 * gi + bi) as u8
+    }
+}
+
+// RGB value of an xterm 256 color palette index (16-255)
+pub fn xterm256_color(idx: u8) -> Color {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    if idx >= 232 {
+        let v = 8 + (idx - 232) * 10;
+        return Color::from_rgba8(v, v, v, 255);
+    }
+
+    let i = idx.max(16) - 16;
+    Color::from_rgba8(
+        LEVELS[(i / 36) as usize],
+        LEVELS[(i / 6 % 6) as usize],
+        LEVELS[(i % 6) as usize],
+        255,
+    )
+}
+
+// Quantize a row of colors to the xterm 256 palette, spreading the error
+// horizontally. row is used to offset the ordered dither pattern.
+pub fn dither(colors: &[Color], method: Dither, row: usize) -> Vec<Color> {
+    const BAYER: [[f32; 4]; 4] = [
+        [0.0, 8.0, 2.0, 10.0],
+        [12.0, 4.0, 14.0, 6.0],
+        [3.0, 11.0, 1.0, 9.0],
+        [15.0, 7.0, 13.0, 5.0],
+    ];
+    // Roughly the distance between color cube levels
+    const SPREAD: f32 = 40.0 / 255.0;
+
+    let quantize = |r: f32, g: f32, b: f32| {
+        xterm256_color(xterm256(&Color::new(
+            r.clamp(0.0, 1.0),
+            g.clamp(0.0, 1.0),
+            b.clamp(0.0, 1.0),
+            1.0,
+        )))
+    };
+
+    match method {
+        Dither::None => colors.to_vec(),
+
+        Dither::Ordered => colors
+            .iter()
+            .enumerate()
+            .map(|(x, c)| {
+                let d = (BAYER[row % 4][x % 4] / 16.0 - 0.5) * SPREAD;
+                quantize(c.r + d, c.g + d, c.b + d)
+            })
+            .collect(),
+
+        Dither::ErrorDiffusion => {
+            let mut err = [0.0; 3];
+            colors
+                .iter()
+                .map(|c| {
+                    let target = [c.r + err[0], c.g + err[1], c.b + err[2]];
+                    let q = quantize(target[0], target[1], target[2]);
+                    err = [target[0] - q.r, target[1] - q.g, target[2] - q.b];
+                    q
+                })
+                .collect()
+        }
+    }
+}
+
+// Color from an OSC 11 reply, e.g. "\x1B]11;rgb:1e1e/1e1e/2e2e\x07"
+pub fn parse_osc_color(s: &str) -> Option<Color> {
+    let start = s.find("rgb:")? + 4;
+    let body = s[start..].trim_end_matches(['\x07', '\\', '\x1B']);
+
+    let mut ch = [0.0; 3];
+    let mut parts = body.split('/');
+    for c in &mut ch {
+        let hex = parts.next()?;
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let max = (1u32 << (hex.len() * 4)) - 1;
+        *c = u32::from_str_radix(hex, 16).ok()? as f32 / max as f32;
+    }
+
+    Some(Color::new(ch[0], ch[1], ch[2], 1.0))
+}
+
+// Ask the terminal for its background color (OSC 11), None if there is no reply
+#[cfg(unix)]
+pub fn terminal_background() -> Option<Color> {
+    use std::io::{IsTerminal, Read, Write};
+    use std::process::{Command, Stdio};
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    if !tty.is_terminal() {
+        return None;
+    }
+
+    let stty = |args: &[&str]| {
+        Command::new("stty")
+            .args(args)
+            .stdin(std::fs::File::open("/dev/tty").ok()?)
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+    };
+
+    let saved = String::from_utf8(stty(&["-g"])?.stdout).ok()?;
+    // Non-canonical, no echo, reads time out after 0.2 s
+    stty(&["-icanon", "-echo", "min", "0", "time", "2"])?;
+
+    let mut reply = Vec::new();
+    if tty
+        .write_all(b"\x1B]11;?\x07")
+        .and_then(|_| tty.flush())
+        .is_ok()
+    {
+        let mut buf = [0; 64];
+        while let Ok(n) = tty.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            reply.extend_from_slice(&buf[..n]);
+            if reply.ends_with(b"\x07") || reply.ends_with(b"\x1B\\") {
+                break;
+            }
+        }
+    }
+
+    stty(&[saved.trim()]);
+    parse_osc_color(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+pub fn terminal_background() -> Option<Color> {
+    None
+}
+
+// Lowest and highest RGB channel if any is outside [0, 1]
+pub fn out_of_gamut(col: &Color) -> Option<(f32, f32)> {
+    const EPSILON: f32 = 1e-4;
+    let min = col.r.min(col.g).min(col.b);
+    let max = col.r.max(col.g).max(col.b);
+    if min < -EPSILON || max > 1.0 + EPSILON {
+        Some((min, max))
+    } else {
+        None
+    }
+}
+
+pub fn warn_out_of_gamut(t: f32, col: &Color) {
+    if let Some((min, max)) = out_of_gamut(col) {
+        eprintln!("Warning: position {t:.4} is out of gamut, channels range {min:.4} .. {max:.4}");
+    }
+}
+
+// Relative luminance (WCAG), using linear RGB
+pub fn relative_luminance(col: &Color) -> f32 {
+    let [r, g, b, _] = col.to_linear_rgba();
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+// Sort colors (and their positions along with them)
+pub fn sort_colors(colors: &mut [Color], positions: &mut [f32], by: SortBy) {
+    let key = |col: &Color| match by {
+        SortBy::None => 0.0,
+        SortBy::Luminance => relative_luminance(col),
+        SortBy::Hue => {
+            let h = col.to_hsla()[0];
+            if h.is_nan() {
+                0.0
+            } else {
+                h
+            }
+        }
+    };
+
+    let mut items: Vec<(f32, Color, f32)> = colors
+        .iter()
+        .zip(positions.iter())
+        .map(|(col, pos)| (key(col), col.clone(), *pos))
+        .collect();
+    items.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    for (i, (_, col, pos)) in items.into_iter().enumerate() {
+        colors[i] = col;
+        positions[i] = pos;
+    }
+}
+
+// WCAG 2.1 contrast ratio [1..21], fg is composited over bg first
+pub fn contrast_ratio(fg: &Color, bg: &Color) -> f32 {
+    let bg = blend_color(bg, &Color::new(1.0, 1.0, 1.0, 1.0));
+    let l1 = relative_luminance(&blend_color(fg, &bg));
+    let l2 = relative_luminance(&bg);
+    (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+}
+
+// Keep colors (and their positions) with a contrast ratio of at least `min` against bg
+pub fn filter_contrast(colors: &mut Vec<Color>, positions: &mut Vec<f32>, bg: &Color, min: f32) {
+    let (keep_colors, keep_pos) = colors
+        .drain(..)
+        .zip(positions.drain(..))
+        .filter(|(c, _)| contrast_ratio(c, bg) >= min)
+        .unzip();
+    *colors = keep_colors;
+    *positions = keep_pos;
+}
+
+// CSV field quoted per RFC 4180 when it has a comma, quote or line break
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// File name without directories, for --compare labels
+pub fn file_label(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+// Pad or truncate (with an ellipsis) to exactly `width` chars
+pub fn fit_label(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return format!("{s:<width$}");
+    }
+    let mut res: String = s.chars().take(width.saturating_sub(1)).collect();
+    res.push('\u{2026}');
+    res
+}
+
+// Short number label, e.g. 0.0, 0.25, 100.0
+fn ruler_label(v: f32) -> String {
+    let s = format!("{v:.3}");
+    let s = s.trim_end_matches('0');
+    if s.ends_with('.') {
+        format!("{s}0")
+    } else {
+        s.to_string()
+    }
+}
+
+// Tick line and label line (min, mid, max) for a gradient bar `width` cells wide
+pub fn ruler(width: usize, dmin: f32, dmax: f32) -> (String, String) {
+    let last = width.saturating_sub(1);
+    let mid = last / 2;
+
+    let ticks: String = (0..width)
+        .map(|x| {
+            if x == 0 {
+                '\u{2514}'
+            } else if x == last {
+                '\u{2518}'
+            } else if x == mid || x == last / 4 || x == last * 3 / 4 {
+                '\u{2534}'
+            } else {
+                '\u{2500}'
+            }
+        })
+        .collect();
+
+    let mut labels = vec![' '; width];
+    let mut put = |text: &str, start: usize| {
+        for (i, c) in text.chars().enumerate() {
+            if let Some(cell) = labels.get_mut(start + i) {
+                *cell = c;
+            }
+        }
+    };
+
+    let min = ruler_label(dmin);
+    let max = ruler_label(dmax);
+    let center = ruler_label((dmin + dmax) / 2.0);
+    put(&min, 0);
+    put(&max, width.saturating_sub(max.len()));
+
+    // Midpoint label only if it leaves a gap on both sides
+    let start = mid.saturating_sub(center.len() / 2);
+    if start > min.len() && start + center.len() < width.saturating_sub(max.len()) {
+        put(&center, start);
+    }
+
+    (ticks, labels.into_iter().collect())
+}
+
+// One cell of the gradient bar, single color per cell
+pub fn bar_cell(col: &Color, style: BarChar, truecolor: bool) -> String {
+    const SHADE: [char; 3] = ['\u{2591}', '\u{2592}', '\u{2593}'];
+    const ASCII: [char; 8] = [' ', '.', ':', '-', '=', '+', '*', '#'];
+
+    let lum = relative_luminance(col).clamp(0.0, 1.0);
+
+    let ch = match style {
+        BarChar::Shade => SHADE[((lum * 3.0) as usize).min(2)],
+        BarChar::Ascii => ASCII[(lum * 7.0).round() as usize],
+        _ => '\u{2588}',
+    };
+
+    format!("\x1B[{}m{ch}", sgr_color(38, col, truecolor))
+}
+
+pub fn fmt_color(col: &Color, cb: &[Color; 2], width: usize, truecolor: bool) -> String {
     let mut ss = "".to_string();
     for i in 0..width {
         let ch = if (i & 1) == 0 { "\u{2580}" } else { "\u{2584}" };
-        let cl = blend_color(col, &cb[0]).to_rgba8();
-        let cr = blend_color(col, &cb[1]).to_rgba8();
+        let cl = blend_color(col, &cb[0]);
+        let cr = blend_color(col, &cb[1]);
         ss.push_str(&format!(
-            "\x1B[38;2;{};{};{};48;2;{};{};{}m{}",
-            cl[0], cl[1], cl[2], cr[0], cr[1], cr[2], ch
+            "\x1B[{};{}m{}",
+            sgr_color(38, &cl, truecolor),
+            sgr_color(48, &cr, truecolor),
+            ch
         ));
     }
     ss.push_str("\x1B[39;49m");
     ss
 }
 
-fn format_alpha(a: f32) -> String {
-    let s = format!(",{:.2}%", a * 100.0);
+fn format_alpha(a: f32, p: usize) -> String {
+    let s = format!(",{:.p$}%", a * 100.0);
     if s.starts_with(",100") {
         return "".to_string();
     }
     s
 }
 
-pub fn format_color(col: &Color, format: OutputColor) -> String {
+// Hex color, optionally without '#' and/or in uppercase
+pub fn format_hex(col: &Color, alpha: HexAlpha, hash: bool, uppercase: bool) -> String {
+    let [r, g, b, a] = col.to_rgba8();
+    let with_alpha = match alpha {
+        HexAlpha::Auto => a < 255,
+        HexAlpha::Always => true,
+        HexAlpha::Never => false,
+    };
+    let mut s = format!("#{r:02x}{g:02x}{b:02x}");
+    if with_alpha {
+        s.push_str(&format!("{a:02x}"));
+    }
+    let s = if hash { &s[..] } else { &s[1..] };
+    if uppercase {
+        s.to_uppercase()
+    } else {
+        s.to_string()
+    }
+}
+
+// Color as a 0x prefixed 32-bit integer with explicit alpha
+pub fn format_packed(col: &Color, order: PackedOrder) -> String {
+    let [r, g, b, a] = col.to_rgba8();
+    let n = match order {
+        PackedOrder::Rgba => u32::from_be_bytes([r, g, b, a]),
+        PackedOrder::Argb => u32::from_be_bytes([a, r, g, b]),
+    };
+    format!("{n:#010x}")
+}
+
+// Float formats use `precision` decimal places, by default 2 (4 for oklab a, b and oklch chroma)
+pub fn format_color(col: &Color, format: OutputColor, precision: Option<usize>) -> String {
+    let p = precision.unwrap_or(2);
+    let p4 = precision.unwrap_or(4);
     match format {
         OutputColor::Hex => col.to_hex_string(),
 
         OutputColor::Rgb => {
             format!(
-                "rgb({:.2}%,{:.2}%,{:.2}%{})",
+                "rgb({:.p$}%,{:.p$}%,{:.p$}%{})",
                 col.r * 100.0,
                 col.g * 100.0,
                 col.b * 100.0,
-                format_alpha(col.a)
+                format_alpha(col.a, p)
             )
         }
 
         OutputColor::Rgb255 => {
             let [r, g, b, _] = col.to_rgba8();
-            format!("rgb({r},{g},{b}{})", format_alpha(col.a))
+            format!("rgb({r},{g},{b}{})", format_alpha(col.a, p))
         }
 
         OutputColor::Hsl => {
             let [h, s, l, a] = col.to_hsla();
             format!(
-                "hsl({:.2},{:.2}%,{:.2}%{})",
+                "hsl({:.p$},{:.p$}%,{:.p$}%{})",
                 h,
                 s * 100.0,
                 l * 100.0,
-                format_alpha(a)
+                format_alpha(a, p)
             )
         }
 
         OutputColor::Hsv => {
             let [h, s, v, a] = col.to_hsva();
             format!(
-                "hsv({:.2},{:.2}%,{:.2}%{})",
+                "hsv({:.p$},{:.p$}%,{:.p$}%{})",
                 h,
                 s * 100.0,
                 v * 100.0,
-                format_alpha(a)
+                format_alpha(a, p)
             )
         }
 
         OutputColor::Hwb => {
             let [h, w, b, a] = col.to_hwba();
             format!(
-                "hwb({:.2},{:.2}%,{:.2}%{})",
+                "hwb({:.p$},{:.p$}%,{:.p$}%{})",
                 h,
                 w * 100.0,
                 b * 100.0,
-                format_alpha(a)
+                format_alpha(a, p)
+            )
+        }
+
+        OutputColor::Lab => {
+            let [l, a, b, alpha] = col.to_laba();
+            format!(
+                "lab({:.p$}%,{:.p$},{:.p$}{})",
+                l,
+                a,
+                b,
+                format_alpha(alpha, p)
+            )
+        }
+
+        OutputColor::Lch => {
+            let [l, a, b, alpha] = col.to_laba();
+            let (c, h) = to_polar(a, b);
+            format!(
+                "lch({:.p$}%,{:.p$},{:.p$}{})",
+                l,
+                c,
+                h,
+                format_alpha(alpha, p)
+            )
+        }
+
+        OutputColor::Oklab => {
+            let [l, a, b, alpha] = col.to_oklaba();
+            format!(
+                "oklab({:.p$}%,{:.p4$},{:.p4$}{})",
+                l * 100.0,
+                a,
+                b,
+                format_alpha(alpha, p)
             )
         }
+
+        OutputColor::Oklch => {
+            let [l, a, b, alpha] = col.to_oklaba();
+            let (c, h) = to_polar(a, b);
+            format!(
+                "oklch({:.p$}%,{:.p4$},{:.p$}{})",
+                l * 100.0,
+                c,
+                h,
+                format_alpha(alpha, p)
+            )
+        }
+
+        OutputColor::Name => nearest_named_color(col).to_string(),
+
+        OutputColor::Packed => format_packed(col, PackedOrder::Rgba),
+    }
+}
+
+// (chroma, hue in degrees [0, 360)) from rectangular a, b
+fn to_polar(a: f32, b: f32) -> (f32, f32) {
+    let c = (a * a + b * b).sqrt();
+    if c < 1e-4 {
+        return (0.0, 0.0);
+    }
+    (c, b.atan2(a).to_degrees().rem_euclid(360.0))
+}
+
+// JSON object {"hex":..,"rgb":[..],"position":..}
+pub fn json_color(col: &Color, pos: f32) -> String {
+    let [r, g, b, _] = col.to_rgba8();
+    format!(
+        "{{\"hex\":\"{}\",\"rgb\":[{r},{g},{b}],\"position\":{pos}}}",
+        col.to_hex_string()
+    )
+}
+
+// Color stops of a CSS gradient, "conic-gradient(from 0deg, red, blue)" -> "red, blue".
+// The direction / shape argument is dropped, plain stop lists are returned as is.
+pub fn css_stops(s: &str) -> String {
+    let s = s.trim();
+    let lower = s.to_lowercase();
+    let lower = lower.strip_prefix("repeating-").unwrap_or(&lower);
+
+    let is_wrapped = ["linear-gradient(", "radial-gradient(", "conic-gradient("]
+        .iter()
+        .any(|p| lower.starts_with(p))
+        && s.ends_with(')');
+    if !is_wrapped {
+        return s.to_string();
+    }
+
+    let inner = &s[s.find('(').unwrap() + 1..s.len() - 1];
+
+    // First argument at top level (not inside rgb(...) etc.)
+    let mut depth = 0;
+    let mut first_end = inner.len();
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                first_end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    // "to right", "45deg", "from 90deg at 50% 50%", "circle at center", ...
+    let first = inner[..first_end].trim();
+    let word = first.split_whitespace().next().unwrap_or_default();
+    let word = match (word.find('('), first.find(')')) {
+        (Some(_), Some(end)) => &first[..=end],
+        _ => word,
+    };
+
+    if first_end < inner.len() && word.parse::<Color>().is_err() {
+        inner[first_end + 1..].trim().to_string()
+    } else {
+        inner.trim().to_string()
+    }
+}
+
+// Numbers separated by commas and/or whitespace, "0, 0.5 1"
+pub fn parse_floats(s: &str) -> Result<Vec<f32>, String> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            t.parse::<f32>()
+                .map_err(|_| format!("invalid number '{t}'"))
+        })
+        .collect()
+}
+
+// Stop positions like GradientBuilder::domain(): none (0..1), [min, max] or one per color
+pub fn stop_positions(n: usize, pos: Option<&[f32]>) -> Result<Vec<f32>, String> {
+    let pos = match pos {
+        None => linspace(0.0, 1.0, n),
+        Some(p) if p.len() == n => p.to_vec(),
+        Some(p) if p.len() == 2 => linspace(p[0], p[1], n),
+        Some(p) => {
+            return Err(format!(
+                "{} positions for {} colors, expected 2 or {}",
+                p.len(),
+                n,
+                n
+            ))
+        }
+    };
+
+    if pos.windows(2).any(|w| w[0] > w[1]) || pos[0] >= pos[n - 1] {
+        return Err("positions must be ascending".to_string());
+    }
+
+    Ok(pos)
+}
+
+// Centers of N equal cells between min and max
+pub fn cell_centers(min: f32, max: f32, n: usize) -> Vec<f32> {
+    (0..n)
+        .map(|i| min + (i as f32 + 0.5) * (max - min) / n as f32)
+        .collect()
+}
+
+// N evenly spaced values from min to max (inclusive)
+pub fn linspace(min: f32, max: f32, n: usize) -> Vec<f32> {
+    if n == 1 {
+        return vec![min];
+    }
+    (0..n)
+        .map(|i| min + (i as f32 * (max - min)) / (n - 1) as f32)
+        .collect()
+}
+
+// Positions sampled along the gradient and the cumulative CIELAB delta E at each
+fn arc_lengths(grad: &dyn Gradient, steps: usize) -> (Vec<f32>, Vec<f32>) {
+    let (dmin, dmax) = grad.domain();
+    let ts = linspace(dmin, dmax, steps + 1);
+    let mut dist = Vec::with_capacity(ts.len());
+    let mut total = 0.0;
+    let mut prev = grad.at(dmin).clamp();
+
+    for t in &ts {
+        let col = grad.at(*t).clamp();
+        total += delta_e(&prev, &col);
+        dist.push(total);
+        prev = col;
+    }
+
+    (ts, dist)
+}
+
+// n positions evenly spaced by CIELAB delta E along the gradient
+pub fn perceptual_positions(grad: &dyn Gradient, n: usize) -> Vec<f32> {
+    const STEPS: usize = 1000;
+
+    let (dmin, dmax) = grad.domain();
+    let (ts, dist) = arc_lengths(grad, STEPS);
+    let total = dist[STEPS];
+
+    // Single color gradient
+    if n < 2 || total < 1e-3 {
+        return linspace(dmin, dmax, n);
+    }
+
+    let mut i = 0;
+
+    (0..n)
+        .map(|k| {
+            let target = total * k as f32 / (n - 1) as f32;
+            while i < STEPS - 1 && dist[i + 1] < target {
+                i += 1;
+            }
+            let d = dist[i + 1] - dist[i];
+            if d > 0.0 {
+                ts[i] + (target - dist[i]).clamp(0.0, d) / d * (ts[i + 1] - ts[i])
+            } else {
+                ts[i]
+            }
+        })
+        .collect()
+}
+
+pub struct Stats {
+    pub darkest: Color,
+    pub lightest: Color,
+    pub mean: Color,
+    // Total CIELAB delta E along the gradient
+    pub arclength: f32,
+}
+
+// Darkest / lightest by relative luminance, mean in linear RGB
+pub fn gradient_stats(grad: &dyn Gradient) -> Stats {
+    const STEPS: usize = 1000;
+
+    let (ts, dist) = arc_lengths(grad, STEPS);
+    let first = grad.at(ts[0]).clamp();
+    let mut darkest = (relative_luminance(&first), first.clone());
+    let mut lightest = darkest.clone();
+    let mut sum = [0.0; 4];
+
+    for t in &ts {
+        let col = grad.at(*t).clamp();
+        let lum = relative_luminance(&col);
+        if lum < darkest.0 {
+            darkest = (lum, col.clone());
+        }
+        if lum > lightest.0 {
+            lightest = (lum, col.clone());
+        }
+        for (s, v) in sum.iter_mut().zip(col.to_linear_rgba()) {
+            *s += v;
+        }
+    }
+
+    let n = ts.len() as f32;
+    let mean = Color::from_linear_rgba(sum[0] / n, sum[1] / n, sum[2] / n, sum[3] / n);
+
+    Stats {
+        darkest: darkest.1,
+        lightest: lightest.1,
+        mean,
+        arclength: dist[STEPS],
+    }
+}
+
+// Count of densely sampled colors per hue bin, bin 0 is centered on red. Colors
+// with (near) zero saturation have no meaningful hue and are counted as gray.
+pub fn hue_histogram(grad: &dyn Gradient, bins: usize) -> (Vec<usize>, usize) {
+    const STEPS: usize = 1000;
+
+    let (dmin, dmax) = grad.domain();
+    let width = 360.0 / bins as f32;
+    let mut counts = vec![0; bins];
+    let mut gray = 0;
+
+    for t in linspace(dmin, dmax, STEPS) {
+        let [h, s, v, _] = grad.at(t).clamp().to_hsva();
+        if h.is_nan() || s < 0.1 || v < 0.05 {
+            gray += 1;
+            continue;
+        }
+        let i = ((h.rem_euclid(360.0) + width / 2.0) / width) as usize % bins;
+        counts[i] += 1;
+    }
+
+    (counts, gray)
+}
+
+// Color of a blackbody at `kelvin` (1667K to 25000K), from the Kim et al. cubic spline
+// approximation of the Planckian locus, brightest channel scaled to 1
+pub fn kelvin_to_rgb(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1667.0, 25000.0) as f64;
+    let (t2, t3) = (t * t, t * t * t);
+
+    let x = if t <= 4000.0 {
+        -0.2661239e9 / t3 - 0.2343589e6 / t2 + 0.8776956e3 / t + 0.179910
+    } else {
+        -3.0258469e9 / t3 + 2.1070379e6 / t2 + 0.2226347e3 / t + 0.240390
+    };
+    let (x2, x3) = (x * x, x * x * x);
+    let y = if t <= 2222.0 {
+        -1.1063814 * x3 - 1.34811020 * x2 + 2.18555832 * x - 0.20219683
+    } else if t <= 4000.0 {
+        -0.9549476 * x3 - 1.37418593 * x2 + 2.09137015 * x - 0.16748867
+    } else {
+        3.0817580 * x3 - 5.87338670 * x2 + 3.75112997 * x - 0.37001483
+    };
+
+    // xyY (Y = 1) -> XYZ -> linear sRGB
+    let (cx, cy, cz) = (x / y, 1.0, (1.0 - x - y) / y);
+    let rgb = [
+        3.2404542 * cx - 1.5371385 * cy - 0.4985314 * cz,
+        -0.9692660 * cx + 1.8760108 * cy + 0.0415560 * cz,
+        0.0556434 * cx - 0.2040259 * cy + 1.0572252 * cz,
+    ]
+    .map(|c: f64| c.max(0.0));
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+
+    Color::from_linear_rgba(
+        (rgb[0] / max) as f32,
+        (rgb[1] / max) as f32,
+        (rgb[2] / max) as f32,
+        1.0,
+    )
+}
+
+// Blackbody colors from min to max kelvin across the domain [0, 1]
+#[derive(Clone)]
+pub struct Blackbody {
+    min: f32,
+    max: f32,
+}
+
+impl Blackbody {
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
     }
 }
 
+impl Gradient for Blackbody {
+    fn at(&self, t: f32) -> Color {
+        kelvin_to_rgb(self.min + (self.max - self.min) * t.clamp(0.0, 1.0))
+    }
+}
+
+// Channel names, nominal ranges and values of a color for --plot, alpha last.
+// Values are not clamped so interpolation overshoot stays visible.
+pub fn plot_channels(col: &Color, space: PlotSpace) -> [(&'static str, f32, f32, f32); 4] {
+    match space {
+        PlotSpace::Rgb => [
+            ("R", 0.0, 1.0, col.r),
+            ("G", 0.0, 1.0, col.g),
+            ("B", 0.0, 1.0, col.b),
+            ("alpha", 0.0, 1.0, col.a),
+        ],
+        PlotSpace::Hsl => {
+            let [h, s, l, a] = col.to_hsla();
+            [
+                ("H", 0.0, 360.0, if h.is_nan() { 0.0 } else { h }),
+                ("S", 0.0, 1.0, s),
+                ("L", 0.0, 1.0, l),
+                ("alpha", 0.0, 1.0, a),
+            ]
+        }
+        PlotSpace::Oklab => {
+            let [l, a, b, alpha] = col.to_oklaba();
+            [
+                ("L", 0.0, 1.0, l),
+                ("a", -0.4, 0.4, a),
+                ("b", -0.4, 0.4, b),
+                ("alpha", 0.0, 1.0, alpha),
+            ]
+        }
+    }
+}
+
+// Line chart of values (two per char) in `rows` lines of braille, min at the bottom
+pub fn braille_plot(values: &[f32], min: f32, max: f32, rows: usize) -> Vec<String> {
+    // Dot bits of the left and right column, top to bottom
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+    let height = rows * 4;
+    let cols = values.len().div_ceil(2);
+    let mut cells = vec![vec![0u32; cols]; rows];
+    let dot_y = |v: f32| {
+        let y = (max - v) / (max - min) * (height - 1) as f32;
+        (y.round().max(0.0) as usize).min(height - 1)
+    };
+
+    let mut prev = None;
+    for (x, v) in values.iter().enumerate() {
+        let y = dot_y(*v);
+        // Fill the vertical gap to the previous sample so steep parts stay connected
+        let (a, b) = match prev {
+            Some(p) if p < y => (p + 1, y),
+            Some(p) if p > y => (y, p - 1),
+            _ => (y, y),
+        };
+        for yy in a..=b {
+            cells[yy / 4][x / 2] |= DOTS[x % 2][yy % 4];
+        }
+        prev = Some(y);
+    }
+
+    cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|bits| char::from_u32(0x2800 + bits).unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+// Braille cell with the left and right dot columns filled by level (0..1) through an
+// ordered dither, 0 is blank and 1 is all 8 dots
+pub fn braille_cell(left: f32, right: f32) -> char {
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    const ORDER: [[u32; 4]; 2] = [[0, 6, 1, 7], [4, 2, 5, 3]];
+
+    let mut bits = 0;
+    for (col, level) in [left, right].iter().enumerate() {
+        for row in 0..4 {
+            if *level > (ORDER[col][row] as f32 + 0.5) / 8.0 {
+                bits |= DOTS[col][row];
+            }
+        }
+    }
+    char::from_u32(0x2800 + bits).unwrap()
+}
+
+// Gradient precomputed into `size` evenly spaced colors, linearly interpolated
+#[derive(Clone)]
+pub struct Lut {
+    colors: Vec<[f32; 4]>,
+    dmin: f32,
+    dmax: f32,
+}
+
+impl Lut {
+    pub fn new(grad: &dyn Gradient, size: usize) -> Self {
+        let (dmin, dmax) = grad.domain();
+        let colors = linspace(dmin, dmax, size.max(2))
+            .iter()
+            .map(|t| {
+                let c = grad.at(*t);
+                [c.r, c.g, c.b, c.a]
+            })
+            .collect();
+        Self { colors, dmin, dmax }
+    }
+}
+
+impl Gradient for Lut {
+    fn at(&self, t: f32) -> Color {
+        let last = self.colors.len() - 1;
+        let x = remap(t, self.dmin, self.dmax, 0.0, last as f32);
+        if x.is_nan() || x <= 0.0 {
+            let [r, g, b, a] = self.colors[0];
+            return Color::new(r, g, b, a);
+        }
+        if x >= last as f32 {
+            let [r, g, b, a] = self.colors[last];
+            return Color::new(r, g, b, a);
+        }
+
+        let i = x as usize;
+        let f = x - i as f32;
+        let (c0, c1) = (self.colors[i], self.colors[i + 1]);
+        let lerp = |k: usize| c0[k] + (c1[k] - c0[k]) * f;
+        Color::new(lerp(0), lerp(1), lerp(2), lerp(3))
+    }
+
+    fn domain(&self) -> (f32, f32) {
+        (self.dmin, self.dmax)
+    }
+}
+
+// GIMP gradient with linear RGB segments between evenly spaced colors
+pub fn to_ggr(colors: &[Color], name: &str) -> String {
+    let n = colors.len() - 1;
+    let mut s = format!("GIMP Gradient\nName: {name}\n{n}\n");
+
+    for (i, c) in colors.windows(2).enumerate() {
+        let left = i as f32 / n as f32;
+        let right = (i + 1) as f32 / n as f32;
+        s.push_str(&format!(
+            "{:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} 0 0\n",
+            left,
+            (left + right) / 2.0,
+            right,
+            c[0].r,
+            c[0].g,
+            c[0].b,
+            c[0].a,
+            c[1].r,
+            c[1].g,
+            c[1].b,
+            c[1].a,
+        ));
+    }
+
+    s
+}
+
+// Minimal SVG document with a linearGradient, offsets are in [0, 1]
+pub fn to_svg(colors: &[Color], offsets: &[f32], angle: f32) -> String {
+    let mut s = String::from(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1000\" height=\"100\">\n  <linearGradient id=\"gradient\"",
+    );
+
+    // CSS angle to gradient vector, left to right (the SVG default) is 90deg
+    if (angle - 90.0).rem_euclid(360.0) > 1e-3 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        // + 0.0 turns -0 into 0
+        let p = |v: f32| (v * 100.0).round() / 100.0 + 0.0;
+        s.push_str(&format!(
+            " x1=\"{}%\" y1=\"{}%\" x2=\"{}%\" y2=\"{}%\"",
+            p(50.0 - 50.0 * sin),
+            p(50.0 + 50.0 * cos),
+            p(50.0 + 50.0 * sin),
+            p(50.0 - 50.0 * cos)
+        ));
+    }
+    s.push_str(">\n");
+
+    for (c, t) in colors.iter().zip(offsets) {
+        let [r, g, b, a] = c.to_rgba8();
+        let pct = (t * 100.0 * 100.0).round() / 100.0;
+        s.push_str(&format!(
+            "    <stop offset=\"{pct}%\" stop-color=\"#{r:02x}{g:02x}{b:02x}\""
+        ));
+        if a < 255 {
+            s.push_str(&format!(" stop-opacity=\"{:.3}\"", c.a));
+        }
+        s.push_str("/>\n");
+    }
+
+    s.push_str("  </linearGradient>\n  <rect width=\"100%\" height=\"100%\" fill=\"url(#gradient)\"/>\n</svg>\n");
+    s
+}
+
+// Builder calls for colors and optional positions
+pub fn rust_colors(colors: &[Color], pos: Option<&[f32]>) -> String {
+    let colors: Vec<String> = colors
+        .iter()
+        .map(|c| format!("\"{}\"", c.to_hex_string()))
+        .collect();
+    let mut s = format!(".html_colors(&[{}])", colors.join(", "));
+
+    if let Some(pos) = pos {
+        let pos: Vec<String> = pos.iter().map(|t| format!("{t:?}")).collect();
+        s.push_str(&format!("\n    .domain(&[{}])", pos.join(", ")));
+    }
+
+    s
+}
+
+// colorgrad GradientBuilder expression, setup is the colors / css call
+pub fn rust_builder(
+    setup: &str,
+    mode: BlendMode,
+    interpolation: Interpolation,
+) -> Result<String, String> {
+    let mode = match mode {
+        BlendMode::Rgb => "Rgb",
+        BlendMode::LinearRgb => "LinearRgb",
+        BlendMode::Oklab => "Oklab",
+        BlendMode::Lab => "Lab",
+        BlendMode::Oklch | BlendMode::Hsluv | BlendMode::Hsl | BlendMode::Hsv => {
+            return Err(
+                "blend mode oklch, hsluv, hsl and hsv are not available in colorgrad".to_string(),
+            )
+        }
+    };
+
+    let ty = match interpolation {
+        Interpolation::Linear => "LinearGradient",
+        Interpolation::Basis => "BasisGradient",
+        Interpolation::CatmullRom => "CatmullRomGradient",
+        Interpolation::Monotone => {
+            return Err("monotone interpolation is not available in colorgrad".to_string())
+        }
+        Interpolation::Bezier => {
+            return Err("bezier interpolation is not available in colorgrad".to_string())
+        }
+    };
+
+    Ok(format!(
+        "colorgrad::GradientBuilder::new()\n    {setup}\n    .mode(colorgrad::BlendMode::{mode})\n    .build::<colorgrad::{ty}>()?"
+    ))
+}
+
+// Map t in [0, 1] to N cycles of [0, 1). Each seam starts the next cycle,
+// only t = 1 maps to the end color.
+pub fn repeat_t(t: f32, n: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    if t == 1.0 {
+        return 1.0;
+    }
+    (t * n).fract()
+}
+
+// SplitMix64 pseudo-random number generator
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// Add seeded noise in [-amount, amount] to each RGB channel, clamped to [0, 1]
+pub fn jitter_colors(colors: &mut [Color], amount: f32, seed: u64) {
+    let amount = amount.clamp(0.0, 1.0);
+    // Independent of the --random position stream for the same seed
+    let mut rng = SplitMix64::new(seed ^ 0x6a09_e667_f3bc_c909);
+    for col in colors {
+        for c in [&mut col.r, &mut col.g, &mut col.b] {
+            *c = (*c + (rng.next_f32() * 2.0 - 1.0) * amount).clamp(0.0, 1.0);
+        }
+    }
+}
+
+pub fn time_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// Quantize t in [0, 1] into N flat bands, first band is 0 and last is 1
+pub fn step_t(t: f32, n: usize) -> f32 {
+    if n < 2 {
+        return 0.0;
+    }
+    let k = (t.clamp(0.0, 1.0) * n as f32).floor().min((n - 1) as f32);
+    k / (n - 1) as f32
+}
+
+// Triangle wave, map t in [0, 1] to 0 -> 1 -> 0
+pub fn mirror_t(t: f32) -> f32 {
+    1.0 - (2.0 * t.clamp(0.0, 1.0) - 1.0).abs()
+}
+
+// SVG spreadMethod for t outside [0, 1]: repeat cycles and reflect goes back and forth,
+// pad keeps t as is for the gradient to clamp
+pub fn spread_t(t: f32, method: SpreadMethod) -> f32 {
+    match method {
+        SpreadMethod::Pad => t,
+        SpreadMethod::Repeat if t == 1.0 => 1.0,
+        SpreadMethod::Repeat => t.rem_euclid(1.0),
+        SpreadMethod::Reflect => mirror_t(t.rem_euclid(2.0) / 2.0),
+    }
+}
+
+// CSS cubic-bezier(x1, y1, x2, y2) easing of t in [0, 1]
+pub fn cubic_bezier(p: [f32; 4], t: f32) -> f32 {
+    let [x1, y1, x2, y2] = p;
+    let t = t.clamp(0.0, 1.0);
+    let curve = |a: f32, b: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * a + 3.0 * r * s * s * b + s * s * s
+    };
+
+    // Find s with x(s) = t, x is monotonic since x1, x2 are in [0, 1]
+    let (mut lo, mut hi) = (0.0, 1.0);
+    let mut s = t;
+    for _ in 0..32 {
+        let x = curve(x1, x2, s);
+        if (x - t).abs() < 1e-6 {
+            break;
+        }
+        if x < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+
+    curve(y1, y2, s)
+}
+
+// Edit distance between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+
+    prev[b.len()]
+}
+
+// Closest name within edit distance 2
+pub fn suggest<'a>(name: &str, names: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase().replace('_', "-");
+    names
+        .iter()
+        .map(|s| (levenshtein(&name, s), *s))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, s)| s)
+}
+
+// CIE76 color difference (euclidean distance in CIELAB)
+pub fn delta_e(a: &Color, b: &Color) -> f32 {
+    let [l1, a1, b1, _] = a.to_laba();
+    let [l2, a2, b2, _] = b.to_laba();
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+// Pairwise CIELAB delta E, row i column j is the distance from color i to color j
+pub fn distance_matrix(colors: &[Color]) -> Vec<Vec<f32>> {
+    colors
+        .iter()
+        .map(|a| colors.iter().map(|b| delta_e(a, b)).collect())
+        .collect()
+}
+
+// Remove colors too close to the previous kept one, by delta E or (without threshold)
+// same 8-bit RGBA value
+pub fn dedup_colors(colors: &mut Vec<Color>, positions: &mut Vec<f32>, threshold: Option<f32>) {
+    let mut keep_colors: Vec<Color> = Vec::with_capacity(colors.len());
+    let mut keep_pos = Vec::with_capacity(positions.len());
+
+    for (col, pos) in colors.iter().zip(positions.iter()) {
+        if let Some(prev) = keep_colors.last() {
+            let same = match threshold {
+                Some(t) => delta_e(prev, col) <= t,
+                None => prev.to_rgba8() == col.to_rgba8(),
+            };
+            if same {
+                continue;
+            }
+        }
+        keep_colors.push(col.clone());
+        keep_pos.push(*pos);
+    }
+
+    *colors = keep_colors;
+    *positions = keep_pos;
+}
+
+// Closest CSS named color by CIELAB delta E
+pub fn nearest_named_color(col: &Color) -> &'static str {
+    let mut best = ("", f32::INFINITY);
+
+    for (&name, &[r, g, b]) in csscolorparser::NAMED_COLORS.entries() {
+        let d = delta_e(col, &Color::from_rgba8(r, g, b, 255));
+        if d < best.1 || (d == best.1 && name < best.0) {
+            best = (name, d);
+        }
+    }
+
+    best.0
+}
+
+// Color vision deficiency simulation (Vienot et al. 1999), projection in LMS space
+pub fn simulate_cvd(col: &Color, kind: Cvd) -> Color {
+    const RGB_TO_LMS: [[f32; 3]; 3] = [
+        [17.8824, 43.5161, 4.11935],
+        [3.45565, 27.1554, 3.86714],
+        [0.0299566, 0.184309, 1.46709],
+    ];
+    const LMS_TO_RGB: [[f32; 3]; 3] = [
+        [0.080_944_45, -0.130_504_41, 0.116_721_07],
+        [-0.010_248_534, 0.054_019_33, -0.113_614_71],
+        [-0.000_365_296_94, -0.004_121_614_7, 0.693_511_4],
+    ];
+
+    fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+        [
+            m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+            m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+            m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+        ]
+    }
+
+    let [r, g, b, a] = col.to_linear_rgba();
+    let [l, m, s] = mul(&RGB_TO_LMS, [r, g, b]);
+
+    let lms = match kind {
+        Cvd::Protan => [2.02344 * m - 2.52581 * s, m, s],
+        Cvd::Deutan => [l, 0.494207 * l + 1.24827 * s, s],
+        Cvd::Tritan => [l, m, -0.395913 * l + 0.801109 * m],
+    };
+
+    let [r, g, b] = mul(&LMS_TO_RGB, lms);
+    Color::from_linear_rgba(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), a)
+}
+
+// Oklab lightness L -> 1 - L with the same hue, chroma is reduced if out of sRGB gamut
+pub fn invert_lightness(col: &Color) -> Color {
+    let [l, a, b, alpha] = col.to_oklaba();
+    let l = (1.0 - l).clamp(0.0, 1.0);
+
+    let in_gamut = |c: &Color| {
+        [c.r, c.g, c.b]
+            .iter()
+            .all(|v| (-1e-4..=1.0 + 1e-4).contains(v))
+    };
+
+    let res = Color::from_oklaba(l, a, b, alpha);
+    if in_gamut(&res) {
+        return res.clamp();
+    }
+
+    // Binary search for the largest chroma scale still in gamut
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..20 {
+        let mid = (lo + hi) / 2.0;
+        if in_gamut(&Color::from_oklaba(l, a * mid, b * mid, alpha)) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Color::from_oklaba(l, a * lo, b * lo, alpha).clamp()
+}
+
 // Map t from range [a, b] to range [c, d]
 pub fn remap(t: f32, a: f32, b: f32, c: f32, d: f32) -> f32 {
     (t - a) * ((d - c) / (b - a)) + c
 }
+
+// Gradient sampled horizontally into a width x height RGBA image, alpha preserved
+pub fn gradient_image(grad: &dyn Gradient, width: u32, height: u32) -> RgbaImage {
+    let (dmin, dmax) = grad.domain();
+    let row: Vec<u8> = linspace(dmin, dmax, width as usize)
+        .iter()
+        .flat_map(|t| grad.at(*t).to_rgba8())
+        .collect();
+    #[cfg(feature = "parallel")]
+    let buf = repeat_rows_parallel(&row, height as usize);
+    #[cfg(not(feature = "parallel"))]
+    let buf = row.repeat(height as usize);
+    RgbaImage::from_raw(width, height, buf).unwrap()
+}
+
+// Same as row.repeat(n), large buffers are filled in row chunks on multiple threads
+#[cfg(feature = "parallel")]
+fn repeat_rows_parallel(row: &[u8], n: usize) -> Vec<u8> {
+    if row.len() * n < 1 << 20 {
+        return row.repeat(n);
+    }
+
+    let mut buf = vec![0; row.len() * n];
+    let threads = std::thread::available_parallelism().map_or(4, |t| t.get());
+    let chunk = n.div_ceil(threads) * row.len();
+    std::thread::scope(|s| {
+        for part in buf.chunks_mut(chunk) {
+            s.spawn(move || {
+                for dst in part.chunks_exact_mut(row.len()) {
+                    dst.copy_from_slice(row);
+                }
+            });
+        }
+    });
+    buf
+}
+
+// Image encoded as PNG in memory
+pub fn encode_png(img: &RgbaImage) -> image::ImageResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)?;
+    Ok(buf)
+}
+
+// Straight to premultiplied alpha
+pub fn premultiply(col: &Color) -> Color {
+    Color::new(col.r * col.a, col.g * col.a, col.b * col.a, col.a)
+}
+
+pub fn premultiply_image(img: &mut RgbaImage) {
+    for px in img.pixels_mut() {
+        let a = px[3] as u32;
+        for k in 0..3 {
+            px[k] = ((px[k] as u32 * a + 127) / 255) as u8;
+        }
+    }
+}
+
+// Composite image onto an opaque background color
+pub fn flatten_image(img: &mut RgbaImage, bg: &Color) {
+    for px in img.pixels_mut() {
+        let [r, g, b, a] = px.0;
+        *px = Rgba(blend_color(&Color::from_rgba8(r, g, b, a), bg).to_rgba8());
+    }
+}
+
+#[test]
+fn flatten() {
+    let mut img = RgbaImage::from_pixel(2, 1, Rgba([255, 0, 0, 0]));
+    img.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+    flatten_image(&mut img, &Color::new(0.0, 0.0, 1.0, 1.0));
+    assert_eq!(img.get_pixel(0, 0).0, [0, 0, 255, 255]);
+    assert_eq!(img.get_pixel(1, 0).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn braille_dither() {
+    assert_eq!(braille_cell(0.0, 0.0), '\u{2800}');
+    assert_eq!(braille_cell(1.0, 1.0), '\u{28ff}');
+    assert_eq!(braille_cell(1.0, 0.0), '\u{2847}');
+    // Half of the dots, spread over the rows
+    assert_eq!(braille_cell(0.5, 0.5), '\u{2895}');
+    // One more dot per 1/8 of level
+    for i in 0..=8 {
+        let v = i as f32 / 8.0;
+        assert_eq!((braille_cell(v, v) as u32 - 0x2800).count_ones(), i);
+    }
+}
+
+#[test]
+fn csv_quoting() {
+    assert_eq!(csv_field("#ff0000"), "#ff0000");
+    assert_eq!(csv_field("rgb(255,0,0)"), "\"rgb(255,0,0)\"");
+    assert_eq!(csv_field("a \"b\""), "\"a \"\"b\"\"\"");
+}
+
+#[test]
+fn premultiplied() {
+    let c = premultiply(&Color::new(1.0, 0.0, 0.0, 0.5));
+    assert_eq!([c.r, c.g, c.b, c.a], [0.5, 0.0, 0.0, 0.5]);
+    let c = premultiply(&Color::new(0.2, 0.4, 1.0, 1.0));
+    assert_eq!([c.r, c.g, c.b, c.a], [0.2, 0.4, 1.0, 1.0]);
+
+    let mut img = RgbaImage::from_pixel(1, 1, Rgba([255, 100, 0, 128]));
+    premultiply_image(&mut img);
+    assert_eq!(img.get_pixel(0, 0).0, [128, 50, 0, 128]);
+}
+
+#[test]
+fn ggr_round_trip() {
+    use crate::BufReader;
+
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(0.0, 1.0, 0.5, 0.5),
+        Color::new(0.2, 0.4, 1.0, 1.0),
+    ];
+    let ggr = to_ggr(&colors, "Test");
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    let grad =
+        colorgrad::GimpGradient::new(BufReader::new(ggr.as_bytes()), &black, &black).unwrap();
+
+    assert_eq!(grad.name(), "Test");
+
+    for (col, t) in colors.iter().zip([0.0, 0.5, 1.0]) {
+        let c = grad.at(t);
+        assert!((c.r - col.r).abs() < 1e-3);
+        assert!((c.g - col.g).abs() < 1e-3);
+        assert!((c.b - col.b).abs() < 1e-3);
+        assert!((c.a - col.a).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn spread() {
+    assert_eq!(spread_t(1.5, SpreadMethod::Reflect), 0.5);
+    assert_eq!(spread_t(1.75, SpreadMethod::Reflect), 0.25);
+    assert_eq!(spread_t(-0.25, SpreadMethod::Reflect), 0.25);
+    assert_eq!(spread_t(2.0, SpreadMethod::Reflect), 0.0);
+    assert_eq!(spread_t(1.0, SpreadMethod::Reflect), 1.0);
+    assert_eq!(spread_t(1.25, SpreadMethod::Repeat), 0.25);
+    assert_eq!(spread_t(-0.25, SpreadMethod::Repeat), 0.75);
+    assert_eq!(spread_t(1.0, SpreadMethod::Repeat), 1.0);
+    assert_eq!(spread_t(1.5, SpreadMethod::Pad), 1.5);
+}
+
+#[test]
+fn mirror() {
+    assert_eq!(mirror_t(0.0), 0.0);
+    assert_eq!(mirror_t(0.25), 0.5);
+    assert_eq!(mirror_t(0.5), 1.0);
+    assert_eq!(mirror_t(0.75), 0.5);
+    assert_eq!(mirror_t(1.0), 0.0);
+}
+
+#[test]
+fn steps() {
+    assert_eq!(step_t(0.0, 4), 0.0);
+    assert_eq!(step_t(0.3, 4), 1.0 / 3.0);
+    assert_eq!(step_t(0.6, 4), 2.0 / 3.0);
+    assert_eq!(step_t(1.0, 4), 1.0);
+    assert_eq!(step_t(0.7, 1), 0.0);
+}
+
+#[test]
+fn dithering() {
+    // Between two color cube levels (95, 135)
+    let col = Color::from_rgba8(115, 0, 0, 255);
+    let row = vec![col.clone(); 64];
+
+    assert!(dither(&row, Dither::None, 0) == row);
+
+    for method in [Dither::Ordered, Dither::ErrorDiffusion] {
+        // Ordered pattern needs all 4 rows to cover every threshold
+        let out: Vec<_> = (0..4).flat_map(|y| dither(&row, method, y)).collect();
+        assert!(out.iter().all(|c| xterm256_color(xterm256(c)) == *c));
+        assert!(out.iter().any(|c| *c != out[0]));
+
+        let avg = out.iter().map(|c| c.r).sum::<f32>() / out.len() as f32;
+        assert!((avg - col.r).abs() < 0.02);
+    }
+}
+
+#[test]
+fn splitmix() {
+    // Reference values for seed 1234567
+    let mut rng = SplitMix64::new(1234567);
+    assert_eq!(rng.next_u64(), 6457827717110365317);
+    assert_eq!(rng.next_u64(), 3203168211198807973);
+
+    let mut rng = SplitMix64::new(42);
+    assert!((0..1000)
+        .map(|_| rng.next_f32())
+        .all(|t| (0.0..1.0).contains(&t)));
+}
+
+#[test]
+fn xterm_256() {
+    assert_eq!(xterm256(&Color::new(0.0, 0.0, 0.0, 1.0)), 16);
+    assert_eq!(xterm256(&Color::new(1.0, 1.0, 1.0, 1.0)), 231);
+    assert_eq!(xterm256(&Color::new(1.0, 0.0, 0.0, 1.0)), 196);
+    assert_eq!(xterm256(&Color::from_rgba8(128, 128, 128, 255)), 244);
+}
+
+#[test]
+fn preset_suggestion() {
+    use crate::PRESET_NAMES;
+
+    assert_eq!(levenshtein("virids", "viridis"), 1);
+    assert_eq!(levenshtein("magna", "magma"), 1);
+    assert_eq!(levenshtein("", "abc"), 3);
+    assert_eq!(suggest("virids", &PRESET_NAMES), Some("viridis"));
+    assert_eq!(suggest("magna", &PRESET_NAMES), Some("magma"));
+    assert_eq!(suggest("Rd_Bu", &PRESET_NAMES), Some("rd-bu"));
+    assert_eq!(suggest("foobar", &PRESET_NAMES), None);
+}
+
+#[test]
+fn rgb_formats() {
+    let red = Color::from_html("#ff0000").unwrap();
+    assert_eq!(
+        format_color(&red, OutputColor::Rgb255, None),
+        "rgb(255,0,0)"
+    );
+    assert_eq!(
+        format_color(&red, OutputColor::Rgb, None),
+        "rgb(100.00%,0.00%,0.00%)"
+    );
+
+    let c = Color::new(0.5, 0.25, 1.0, 1.0);
+    assert_eq!(
+        format_color(&c, OutputColor::Rgb, Some(4)),
+        "rgb(50.0000%,25.0000%,100.0000%)"
+    );
+    assert_eq!(
+        format_color(&c, OutputColor::Rgb, Some(1)),
+        "rgb(50.0%,25.0%,100.0%)"
+    );
+    assert_eq!(
+        format_color(&red, OutputColor::Hsl, Some(0)),
+        "hsl(0,100%,50%)"
+    );
+
+    let c = Color::new(1.0, 0.5, 0.0, 0.5);
+    assert_eq!(
+        format_color(&c, OutputColor::Rgb255, None),
+        "rgb(255,128,0,50.00%)"
+    );
+}
+
+#[test]
+fn packed_formats() {
+    let red = Color::from_html("#ff0000").unwrap();
+    assert_eq!(format_color(&red, OutputColor::Packed, None), "0xff0000ff");
+    assert_eq!(format_packed(&red, PackedOrder::Rgba), "0xff0000ff");
+    assert_eq!(format_packed(&red, PackedOrder::Argb), "0xffff0000");
+
+    let c = Color::from_html("#0a0b0c80").unwrap();
+    assert_eq!(format_packed(&c, PackedOrder::Rgba), "0x0a0b0c80");
+    assert_eq!(format_packed(&c, PackedOrder::Argb), "0x800a0b0c");
+}
+
+#[test]
+fn hex_formats() {
+    let c = Color::from_html("#abc123").unwrap();
+    assert_eq!(
+        format_hex(&c, HexAlpha::Auto, true, false),
+        format_color(&c, OutputColor::Hex, None)
+    );
+    assert_eq!(format_hex(&c, HexAlpha::Auto, true, true), "#ABC123");
+    assert_eq!(format_hex(&c, HexAlpha::Auto, false, true), "ABC123");
+    assert_eq!(format_hex(&c, HexAlpha::Auto, false, false), "abc123");
+
+    let c = Color::new(1.0, 0.0, 0.0, 0.5);
+    assert_eq!(format_hex(&c, HexAlpha::Always, true, false), "#ff000080");
+    assert_eq!(format_hex(&c, HexAlpha::Auto, true, false), "#ff000080");
+    assert_eq!(format_hex(&c, HexAlpha::Never, true, false), "#ff0000");
+
+    let c = Color::new(1.0, 0.0, 0.0, 1.0);
+    assert_eq!(format_hex(&c, HexAlpha::Always, true, false), "#ff0000ff");
+    assert_eq!(format_hex(&c, HexAlpha::Auto, true, false), "#ff0000");
+}
+
+#[test]
+fn rust_snippet() {
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(0.0, 0.0, 1.0, 0.5),
+    ];
+    let setup = rust_colors(&colors, Some(&[0.0, 1.0]));
+    assert_eq!(
+        rust_builder(&setup, BlendMode::Oklab, Interpolation::CatmullRom).unwrap(),
+        "colorgrad::GradientBuilder::new()
+    .html_colors(&[\"#ff0000\", \"#0000ff80\"])
+    .domain(&[0.0, 1.0])
+    .mode(colorgrad::BlendMode::Oklab)
+    .build::<colorgrad::CatmullRomGradient>()?"
+    );
+    assert!(rust_builder(&setup, BlendMode::Hsluv, Interpolation::Linear).is_err());
+}
+
+#[test]
+fn named_color() {
+    let red = Color::from_html("#ff0000").unwrap();
+    assert_eq!(format_color(&red, OutputColor::Name, None), "red");
+    assert_eq!(
+        nearest_named_color(&Color::from_html("#fe0001").unwrap()),
+        "red"
+    );
+    assert_eq!(
+        nearest_named_color(&Color::from_html("#fff").unwrap()),
+        "white"
+    );
+}
+
+#[test]
+fn contrast() {
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    let white = Color::new(1.0, 1.0, 1.0, 1.0);
+    assert!((contrast_ratio(&black, &white) - 21.0).abs() < 0.01);
+    assert!((contrast_ratio(&white, &black) - 21.0).abs() < 0.01);
+    assert!((contrast_ratio(&white, &white) - 1.0).abs() < 0.01);
+    let c = Color::from_html("#767676").unwrap();
+    assert!((contrast_ratio(&c, &white) - 4.54).abs() < 0.01);
+}
+
+#[test]
+fn cvd_simulation() {
+    let red = Color::new(1.0, 0.0, 0.0, 1.0);
+    let c = simulate_cvd(&red, Cvd::Deutan);
+    // red looks dark yellow
+    assert!((c.r - c.g).abs() < 0.05);
+    assert!(c.g > 0.4);
+    assert!(c.b < 0.05);
+
+    let white = Color::new(1.0, 1.0, 1.0, 1.0);
+    for kind in [Cvd::Protan, Cvd::Deutan, Cvd::Tritan] {
+        let c = simulate_cvd(&white, kind);
+        assert!(c.r > 0.95 && c.g > 0.95 && c.b > 0.95);
+    }
+}
+
+#[test]
+fn gamma() {
+    let gray = Color::new(0.5, 0.5, 0.5, 1.0);
+    let c = apply_gamma(&gray, 2.2);
+    assert!((c.r - 0.7297).abs() < 1e-3);
+    assert_eq!(c.r, c.g);
+    assert_eq!(c.a, 1.0);
+    assert_eq!(apply_gamma(&gray, 1.0), gray);
+}
+
+#[test]
+fn perceptual_spacing() {
+    // black -> gray is slow, gray -> white is fast
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#000", "#777", "#fff"])
+        .domain(&[0.0, 0.9, 1.0])
+        .build::<colorgrad::LinearGradient>()
+        .unwrap();
+
+    let pos = perceptual_positions(&grad, 5);
+    assert_eq!(pos.len(), 5);
+    assert_eq!(pos[0], 0.0);
+    assert!((pos[4] - 1.0).abs() < 1e-4);
+    assert!(pos[2] > 0.8);
+
+    let colors: Vec<_> = pos.iter().map(|t| grad.at(*t)).collect();
+    let steps: Vec<_> = colors.windows(2).map(|c| delta_e(&c[0], &c[1])).collect();
+    for s in &steps {
+        assert!((s - steps[0]).abs() < 0.5);
+    }
+
+    let solid = colorgrad::GradientBuilder::new()
+        .html_colors(&["#f00"])
+        .build::<colorgrad::LinearGradient>()
+        .unwrap();
+    assert_eq!(perceptual_positions(&solid, 3), vec![0.0, 0.5, 1.0]);
+}
+
+#[test]
+fn sort_by_luminance() {
+    let mut colors: Vec<Color> = ["#808080", "#fff", "#000", "#ff0", "#00f"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let mut positions = vec![0.0, 0.25, 0.5, 0.75, 1.0];
+    sort_colors(&mut colors, &mut positions, SortBy::Luminance);
+
+    let hex: Vec<_> = colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#000000", "#0000ff", "#808080", "#ffff00", "#ffffff"]);
+    assert_eq!(positions, [0.5, 1.0, 0.0, 0.75, 0.25]);
+    for w in colors.windows(2) {
+        assert!(relative_luminance(&w[0]) <= relative_luminance(&w[1]));
+    }
+
+    sort_colors(&mut colors, &mut positions, SortBy::Hue);
+    assert_eq!(colors[4].to_hex_string(), "#0000ff");
+}
+
+#[test]
+fn dedup() {
+    let colors: Vec<Color> = ["#000", "#000", "#010101", "#fff", "#000"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let positions = vec![0.0, 0.25, 0.5, 0.75, 1.0];
+
+    let (mut c, mut p) = (colors.clone(), positions.clone());
+    dedup_colors(&mut c, &mut p, None);
+    assert_eq!(c.len(), 4);
+    assert_eq!(p, [0.0, 0.5, 0.75, 1.0]);
+
+    let (mut c, mut p) = (colors, positions);
+    dedup_colors(&mut c, &mut p, Some(1.0));
+    assert_eq!(p, [0.0, 0.75, 1.0]);
+}
+
+#[test]
+fn ruler_ticks() {
+    let (ticks, labels) = ruler(21, 0.0, 1.0);
+    assert_eq!(ticks.chars().count(), 21);
+    assert_eq!(labels.len(), 21);
+    assert_eq!(ticks.chars().nth(10), Some('\u{2534}'));
+    assert_eq!(labels, "0.0      0.5      1.0");
+
+    // no room for the midpoint label
+    let (_, labels) = ruler(12, -100.0, 100.0);
+    assert_eq!(labels, "-100.0 100.0");
+}
+
+#[test]
+fn labels() {
+    assert_eq!(fit_label("rainbow", 9), "rainbow  ");
+    assert_eq!(fit_label("cubehelix", 5), "cube\u{2026}");
+    assert_eq!(file_label(std::path::Path::new("a/b/c.ggr")), "c.ggr");
+}
+
+#[test]
+fn invert_lightness_hue() {
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    let white = Color::new(1.0, 1.0, 1.0, 1.0);
+    assert_eq!(invert_lightness(&black).to_hex_string(), "#ffffff");
+    assert_eq!(invert_lightness(&white).to_hex_string(), "#000000");
+
+    let blue = Color::from_rgba8(40, 80, 200, 255);
+    let inv = invert_lightness(&blue);
+    let hue = |c: &Color| {
+        let [_, a, b, _] = c.to_oklaba();
+        b.atan2(a).to_degrees()
+    };
+    assert!((hue(&blue) - hue(&inv)).abs() < 1.0);
+    assert!((blue.to_oklaba()[0] + inv.to_oklaba()[0] - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn osc_color() {
+    let col = parse_osc_color("\x1B]11;rgb:ffff/8080/0000\x07").unwrap();
+    assert_eq!(col.to_rgba8(), [255, 128, 0, 255]);
+    let col = parse_osc_color("\x1B]11;rgb:1e/1e/2e\x1B\\").unwrap();
+    assert_eq!(col.to_hex_string(), "#1e1e2e");
+    assert!(parse_osc_color("").is_none());
+    assert!(parse_osc_color("\x1B]11;rgb:ff/ff\x07").is_none());
+}
+
+#[test]
+fn stats() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#000", "#fff"])
+        .build::<colorgrad::LinearGradient>()
+        .unwrap();
+    let stats = gradient_stats(&grad);
+    assert_eq!(stats.darkest.to_hex_string(), "#000000");
+    assert_eq!(stats.lightest.to_hex_string(), "#ffffff");
+    // black to white in CIELAB is L 0 -> 100
+    assert!((stats.arclength - 100.0).abs() < 0.5);
+    let [r, g, b, _] = stats.mean.to_rgba8();
+    assert!(r == g && g == b);
+}
+
+#[test]
+fn lookup_table() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#f00", "gold", "#0f8", "navy", "#fff"])
+        .build::<colorgrad::CatmullRomGradient>()
+        .unwrap();
+    let lut = Lut::new(&grad, 4096);
+    assert_eq!(lut.domain(), grad.domain());
+
+    for t in linspace(-0.1, 1.1, 997) {
+        let [r1, g1, b1, _] = grad.at(t).to_rgba8();
+        let [r2, g2, b2, _] = lut.at(t).clamp().to_rgba8();
+        assert!(r1.abs_diff(r2) <= 1 && g1.abs_diff(g2) <= 1 && b1.abs_diff(b2) <= 1);
+    }
+}
+
+// Wall-clock comparison, run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn lookup_table_speed() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#f00", "gold", "#0f8", "navy", "#fff"])
+        .build::<colorgrad::CatmullRomGradient>()
+        .unwrap();
+
+    // Large takes are faster through the table
+    let n = 200_000;
+    let start = std::time::Instant::now();
+    let direct = grad.colors(n);
+    let direct_time = start.elapsed();
+    let start = std::time::Instant::now();
+    let table = Lut::new(&grad, 4096).colors(n);
+    let lut_time = start.elapsed();
+    assert_eq!(direct.len(), table.len());
+    assert!(lut_time < direct_time);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_image() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#f00", "gold", "#0f8", "navy"])
+        .build::<colorgrad::CatmullRomGradient>()
+        .unwrap();
+    let row: Vec<u8> = linspace(0.0, 1.0, 1001)
+        .iter()
+        .flat_map(|t| grad.at(*t).to_rgba8())
+        .collect();
+    for n in [1, 7, 263, 997] {
+        assert_eq!(repeat_rows_parallel(&row, n), row.repeat(n));
+    }
+
+    let img = gradient_image(&grad, 1001, 997);
+    assert_eq!(img.as_raw(), &row.repeat(997));
+}
+
+#[test]
+fn css_wrappers() {
+    assert_eq!(css_stops("red, blue 40%, gold"), "red, blue 40%, gold");
+    assert_eq!(css_stops("linear-gradient(red, blue)"), "red, blue");
+    assert_eq!(
+        css_stops("linear-gradient(to right, red, blue)"),
+        "red, blue"
+    );
+    assert_eq!(css_stops("linear-gradient(45deg, red, blue)"), "red, blue");
+    assert_eq!(
+        css_stops("radial-gradient(circle at center, rgb(255,0,0), blue)"),
+        "rgb(255,0,0), blue"
+    );
+    assert_eq!(
+        css_stops("conic-gradient(from 0deg, red 10%, blue)"),
+        "red 10%, blue"
+    );
+    assert_eq!(
+        css_stops("Repeating-Conic-Gradient(from 90deg at 50% 50%, red, blue)"),
+        "red, blue"
+    );
+    assert_eq!(
+        css_stops("conic-gradient(rgb(0 0 0 / 50%) 20%, white)"),
+        "rgb(0 0 0 / 50%) 20%, white"
+    );
+}
+
+#[test]
+fn svg_angle() {
+    let colors = [
+        Color::new(1.0, 0.0, 0.0, 1.0),
+        Color::new(0.0, 0.0, 1.0, 1.0),
+    ];
+    assert!(to_svg(&colors, &[0.0, 1.0], 90.0).contains("<linearGradient id=\"gradient\">"));
+    assert!(
+        to_svg(&colors, &[0.0, 1.0], 180.0).contains("x1=\"50%\" y1=\"0%\" x2=\"50%\" y2=\"100%\"")
+    );
+
+    let angle = |s: &str| s.parse::<crate::cli::Angle>().map(|a| (a.css, a.degrees));
+    assert_eq!(angle("to bottom"), Ok(("to bottom".into(), 180.0)));
+    assert_eq!(angle("To Top Left"), Ok(("to top left".into(), 315.0)));
+    assert_eq!(angle("45deg"), Ok(("45deg".into(), 45.0)));
+    assert_eq!(angle("0.5turn"), Ok(("180deg".into(), 180.0)));
+    assert!(angle("to middle").is_err());
+    assert!(angle("to left right").is_err());
+    assert!(angle("sideways").is_err());
+}
+
+#[test]
+fn float_list() {
+    assert_eq!(parse_floats("0, 0.5 1,\t-2"), Ok(vec![0.0, 0.5, 1.0, -2.0]));
+    assert_eq!(parse_floats("  "), Ok(vec![]));
+    assert_eq!(
+        parse_floats("0.1, x"),
+        Err("invalid number 'x'".to_string())
+    );
+}
+
+#[test]
+fn gamut_check() {
+    assert_eq!(out_of_gamut(&Color::new(0.0, 0.5, 1.0, 1.0)), None);
+    assert_eq!(out_of_gamut(&Color::new(1.00001, 0.5, 0.0, 1.0)), None);
+    assert_eq!(
+        out_of_gamut(&Color::new(-0.25, 0.5, 1.5, 1.0)),
+        Some((-0.25, 1.5))
+    );
+}
+
+#[test]
+fn bezier_easing() {
+    let ease = [0.42, 0.0, 0.58, 1.0];
+    assert_eq!(cubic_bezier(ease, 0.0), 0.0);
+    assert_eq!(cubic_bezier(ease, 1.0), 1.0);
+    assert!((cubic_bezier(ease, 0.5) - 0.5).abs() < 1e-4);
+    assert!(cubic_bezier(ease, 0.25) < 0.25);
+    assert!(cubic_bezier(ease, 0.75) > 0.75);
+
+    // Straight line, no easing
+    let linear = [0.0, 0.0, 1.0, 1.0];
+    for t in [0.0, 0.1, 0.5, 0.9, 1.0] {
+        assert!((cubic_bezier(linear, t) - t).abs() < 1e-4);
+    }
+
+    // CSS ease-in: slow start
+    assert!(cubic_bezier([0.42, 0.0, 1.0, 1.0], 0.5) < 0.5);
+}
+
+#[test]
+fn hue_bins() {
+    let grad = colorgrad::GradientBuilder::new()
+        .html_colors(&["#f00", "#f00", "#808080", "#00f"])
+        .mode(colorgrad::BlendMode::Rgb)
+        .build::<colorgrad::LinearGradient>()
+        .unwrap();
+    let (counts, gray) = hue_histogram(&grad, 12);
+    assert_eq!(counts.len(), 12);
+    assert_eq!(counts.iter().sum::<usize>() + gray, 1000);
+    // Red half, then gray -> blue. Reds dominate and nothing lands in green.
+    assert!(counts[0] > 300);
+    assert!(counts[8] > 0);
+    assert!(gray > 0);
+    assert_eq!(counts[4], 0);
+    assert_eq!(hue_histogram(&grad, 36).0.len(), 36);
+}
+
+#[test]
+fn blackbody() {
+    // D65 white point is close to 6500K
+    let [r, g, b, _] = kelvin_to_rgb(6500.0).to_rgba8();
+    assert!(r >= 245 && g >= 245 && b >= 245);
+
+    assert_eq!(kelvin_to_rgb(1900.0).to_rgba8(), [255, 132, 0, 255]);
+    assert_eq!(kelvin_to_rgb(2700.0).to_rgba8(), [255, 173, 89, 255]);
+    assert_eq!(kelvin_to_rgb(10000.0).to_rgba8(), [205, 217, 255, 255]);
+    // Clamped to the approximation range
+    assert_eq!(
+        kelvin_to_rgb(500.0).to_rgba8(),
+        kelvin_to_rgb(1667.0).to_rgba8()
+    );
+
+    let grad = Blackbody::new(2700.0, 10000.0);
+    assert_eq!(grad.at(0.0).to_rgba8(), [255, 173, 89, 255]);
+    assert_eq!(grad.at(1.0).to_rgba8(), [205, 217, 255, 255]);
+}
+
+#[test]
+fn jitter() {
+    let colors: Vec<Color> = ["#808080", "#ff0000", "#000"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+
+    let mut a = colors.clone();
+    let mut b = colors.clone();
+    jitter_colors(&mut a, 0.1, 7);
+    jitter_colors(&mut b, 0.1, 7);
+    assert_eq!(a, b);
+    assert_ne!(a, colors);
+
+    for (j, c) in a.iter().zip(&colors) {
+        for (x, y) in [(j.r, c.r), (j.g, c.g), (j.b, c.b)] {
+            assert!((x - y).abs() <= 0.1 + 1e-6);
+            assert!((0.0..=1.0).contains(&x));
+        }
+        assert_eq!(j.a, c.a);
+    }
+
+    let mut c = colors.clone();
+    jitter_colors(&mut c, 0.0, 7);
+    assert_eq!(c, colors);
+}
+
+#[test]
+fn braille_chart() {
+    // Flat at the top, then a drop to the bottom
+    let rows = braille_plot(&[1.0, 1.0, 1.0, 0.0], 0.0, 1.0, 2);
+    assert_eq!(rows, ["\u{2809}\u{28b1}", "\u{2800}\u{28b8}"]);
+
+    // Out of range values stick to the edges
+    let rows = braille_plot(&[2.0, -1.0], 0.0, 1.0, 1);
+    assert_eq!(rows, ["\u{28b1}"]);
+
+    let [(name, lo, hi, v), ..] = plot_channels(&Color::new(1.2, 0.0, 0.0, 1.0), PlotSpace::Rgb);
+    assert_eq!((name, lo, hi, v), ("R", 0.0, 1.0, 1.2));
+}
+
+#[test]
+fn distances() {
+    let colors: Vec<Color> = ["#000", "#fff", "#000"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let m = distance_matrix(&colors);
+    assert_eq!(m.len(), 3);
+    assert_eq!(m[0][0], 0.0);
+    assert_eq!(m[0][2], 0.0);
+    assert_eq!(m[0][1], m[1][0]);
+    assert!((m[1][2] - 100.0).abs() < 0.01);
+}
+
+#[test]
+fn centered_positions() {
+    assert_eq!(cell_centers(0.0, 1.0, 1), [0.5]);
+    assert_eq!(cell_centers(0.0, 1.0, 4), [0.125, 0.375, 0.625, 0.875]);
+    assert_eq!(cell_centers(10.0, 20.0, 2), [12.5, 17.5]);
+}
+
+#[test]
+fn contrast_filter() {
+    let mut colors: Vec<Color> = ["#000", "#777", "#767676", "#fff"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let mut positions = vec![0.0, 0.3, 0.6, 1.0];
+    let white = Color::new(1.0, 1.0, 1.0, 1.0);
+    // #767676 is the lightest gray with 4.5:1 on white
+    filter_contrast(&mut colors, &mut positions, &white, 4.5);
+    let hex: Vec<_> = colors.iter().map(|c| c.to_hex_string()).collect();
+    assert_eq!(hex, ["#000000", "#767676"]);
+    assert_eq!(positions, [0.0, 0.6]);
+}
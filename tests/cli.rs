@@ -0,0 +1,806 @@
+use std::process::Command;
+
+fn gradient(args: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(args)
+        .output()
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn reverse() {
+    let fwd = gradient(&["--preset", "rainbow", "--take", "5"]);
+    let rev = gradient(&["--preset", "rainbow", "--take", "5", "--reverse"]);
+    let mut fwd: Vec<_> = fwd.lines().collect();
+    fwd.reverse();
+    assert_eq!(fwd.len(), 5);
+    assert_eq!(fwd, rev.lines().collect::<Vec<_>>());
+}
+
+#[test]
+fn json_output() {
+    let out = gradient(&["--custom", "red", "blue", "--take", "3", "--json"]);
+    assert!(out.starts_with("[{\"hex\":\"#ff0000\",\"rgb\":[255,0,0],\"position\":0}"));
+    assert!(out.contains("\"position\":0.5}"));
+    assert!(out
+        .trim_end()
+        .ends_with("\"hex\":\"#0000ff\",\"rgb\":[0,0,255],\"position\":1}]"));
+
+    let out = gradient(&["--custom", "red", "blue", "--sample", "0.25", "--json"]);
+    assert!(out.contains("\"position\":0.25}"));
+}
+
+#[test]
+fn gpl_palette() {
+    let dir = std::env::temp_dir();
+
+    let path = dir.join("gradient-test-ok.gpl");
+    std::fs::write(
+        &path,
+        "GIMP Palette\nName: Test\nColumns: 2\n#\n255   0   0\tRed\n  0   0 255\tBlue\n",
+    )
+    .unwrap();
+    let out = gradient(&["--file", path.to_str().unwrap(), "--take", "2"]);
+    assert_eq!(out, "#ff0000\n#0000ff\n");
+
+    let path = dir.join("gradient-test-bad.gpl");
+    std::fs::write(&path, "GIMP Palette\n255 0 x\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Invalid palette (line 2)"));
+}
+
+#[test]
+fn info() {
+    let out = gradient(&[
+        "--custom", "red", "blue", "-m", "rgb", "-i", "linear", "--info", "--take", "3",
+    ]);
+    assert_eq!(
+        out,
+        "domain: 0 .. 1\nstops: 3\n0.0000 -> #ff0000\n0.5000 -> #800080\n1.0000 -> #0000ff\n"
+    );
+
+    let out = gradient(&["--file", "data/Neon_Green.ggr", "--info"]);
+    assert!(out.contains("segments: 4\ndomain: 0 .. 1\nstops: 11\n"));
+}
+
+#[test]
+fn sample_keywords() {
+    let args = [
+        "--custom", "red", "blue", "-m", "rgb", "-i", "linear", "-P", "10", "20",
+    ];
+    let out = gradient(&[&args[..], &["--sample", "start,mid,end"]].concat());
+    assert_eq!(out, "#ff0000\n#800080\n#0000ff\n");
+
+    let out = gradient(&[&args[..], &["--sample", "min", "15", "max"]].concat());
+    assert_eq!(out, "#ff0000\n#800080\n#0000ff\n");
+}
+
+#[test]
+fn multiple_presets() {
+    let out = gradient(&["--preset", "rainbow", "viridis", "--take", "2"]);
+    let one = gradient(&["--preset", "viridis", "--take", "2"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "rainbow");
+    assert_eq!(lines[3], "viridis");
+    assert_eq!(lines[4..].join("\n") + "\n", one);
+}
+
+#[test]
+fn compare_labels() {
+    let out = gradient(&["-p", "rainbow", "viridis", "--compare", "-W", "40"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("rainbow \x1B["));
+    assert!(lines[1].starts_with("        \x1B["));
+    assert!(lines[2].starts_with("viridis \x1B["));
+    assert_eq!(lines[0].matches('\u{258C}').count(), 32);
+}
+
+#[test]
+fn mix_presets() {
+    let a = gradient(&["--preset", "rainbow", "--take", "4"]);
+    let b = gradient(&["--preset", "viridis", "--take", "4"]);
+    assert_eq!(
+        gradient(&["--mix", "rainbow", "viridis", "0", "--take", "4"]),
+        a
+    );
+    assert_eq!(
+        gradient(&["--mix", "rainbow", "viridis", "-1", "--take", "4"]),
+        a
+    );
+    assert_eq!(
+        gradient(&["--mix", "rainbow", "viridis", "1", "--take", "4"]),
+        b
+    );
+    assert_ne!(
+        gradient(&["--mix", "rainbow", "viridis", "0.5", "--take", "4"]),
+        a
+    );
+}
+
+#[test]
+fn oklch_format() {
+    let out = gradient(&["--preset", "viridis", "--take", "3", "--format", "oklch"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines
+        .iter()
+        .all(|s| s.starts_with("oklch(") && s.ends_with(')')));
+
+    let out = gradient(&["--custom", "#fff", "#000", "--take", "2", "--format", "lab"]);
+    assert_eq!(out, "lab(100.00%,0.00,0.00)\nlab(0.00%,0.00,0.00)\n");
+}
+
+#[test]
+fn random_seed() {
+    let a = gradient(&["--preset", "rainbow", "--random", "5", "--seed", "7"]);
+    let b = gradient(&["--preset", "rainbow", "--random", "5", "--seed", "7"]);
+    let c = gradient(&["--preset", "rainbow", "--random", "5", "--seed", "8"]);
+    assert_eq!(a.lines().count(), 5);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--preset", "rainbow", "--random", "0"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--random <NUM>"));
+}
+
+#[test]
+fn domain() {
+    let plain = gradient(&["--preset", "rainbow", "--sample", "0", "0.25", "1"]);
+    let out = gradient(&[
+        "--preset", "rainbow", "--domain", "0", "40", "--sample", "0", "10", "40",
+    ]);
+    assert_eq!(out, plain);
+
+    let args = [
+        "--custom", "red", "blue", "-m", "rgb", "-i", "linear", "--domain", "-10", "10",
+    ];
+    let out = gradient(&[&args[..], &["--sample", "0"]].concat());
+    assert_eq!(out, "#800080\n");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--preset", "rainbow", "--domain", "5", "5"])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+}
+
+#[test]
+fn no_hash() {
+    let out = gradient(&["--custom", "red", "blue", "--take", "2", "--no-hash"]);
+    assert_eq!(out, "ff0000\n0000ff\n");
+
+    let out = gradient(&["--custom", "red", "blue", "--take", "2", "--no-hash", "-a"]);
+    assert_eq!(out, "[\"ff0000\", \"0000ff\"]\n");
+
+    let out = gradient(&[
+        "--custom",
+        "red",
+        "--take",
+        "1",
+        "--no-hash",
+        "-o",
+        "rgb255",
+    ]);
+    assert_eq!(out, "rgb(255,0,0)\n");
+}
+
+#[test]
+fn image_format() {
+    let path = std::env::temp_dir().join("gradient-test.bmp");
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--preset", "rainbow", "--output"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Unsupported image format"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn checkerboard_colors() {
+    let args = [
+        "--custom",
+        "#0000",
+        "-W",
+        "10",
+        "-H",
+        "1",
+        "--color-depth",
+        "24bit",
+    ];
+    let red = "38;2;255;0;0;48;2;255;0;0";
+    let blue = "38;2;0;0;255;48;2;0;0;255";
+
+    let out = gradient(&[&args[..], &["--cb-color", "red"]].concat());
+    assert!(out.contains(red));
+    assert!(!out.contains(blue));
+
+    let out = gradient(&[&args[..], &["--cb-color", "red", "blue"]].concat());
+    assert!(out.contains(red));
+    assert!(out.contains(blue));
+}
+
+#[test]
+fn checker_size() {
+    let args = [
+        "--custom",
+        "#0000",
+        "-W",
+        "16",
+        "-H",
+        "1",
+        "--color-depth",
+        "24bit",
+        "--cb-color",
+        "red",
+        "blue",
+    ];
+    // Background of each cell, r for red and b for blue
+    let pattern = |size: &str| -> String {
+        gradient(&[&args[..], &["--checker-size", size]].concat())
+            .split("48;2;")
+            .skip(1)
+            .map(|s| if s.starts_with("255;0;0") { 'r' } else { 'b' })
+            .collect()
+    };
+
+    assert_eq!(pattern("1"), "rbrbrbrbrbrbrbrb");
+    assert_eq!(pattern("4"), "rrrrbbbbrrrrbbbb");
+}
+
+#[test]
+fn animate_not_terminal() {
+    let args = ["--preset", "rainbow", "-W", "20", "--color-depth", "24bit"];
+    let out = gradient(&[&args[..], &["--animate", "--frames", "3"]].concat());
+    assert_eq!(out, gradient(&args));
+}
+
+#[test]
+fn emit_rust() {
+    let out = gradient(&["--preset", "cubehelix", "--emit-rust"]);
+    assert_eq!(out, "colorgrad::preset::cubehelix_default()\n");
+
+    let out = gradient(&["--custom", "red", "blue", "-i", "basis", "--emit-rust"]);
+    assert!(out.contains(".html_colors(&[\"#ff0000\", \"#0000ff\"])"));
+    assert!(out.contains(".build::<colorgrad::BasisGradient>()?"));
+}
+
+#[test]
+fn all_formats() {
+    let out = gradient(&["-c", "red", "blue", "-s", "0", "1", "--all-formats"]);
+    let blocks: Vec<_> = out.split("\n\n").collect();
+    assert_eq!(blocks.len(), 2);
+    let lines: Vec<_> = blocks[0].lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "#ff0000");
+    assert!(lines[1].starts_with("rgb("));
+    assert!(lines[5].starts_with("oklch("));
+}
+
+#[test]
+fn sample_file() {
+    let path = std::env::temp_dir().join("gradient-test-sample-file.txt");
+    std::fs::write(&path, "1, 0\n\n0.5\n").unwrap();
+    let out = gradient(&[
+        "-c",
+        "red",
+        "blue",
+        "-m",
+        "rgb",
+        "--sample-file",
+        path.to_str().unwrap(),
+    ]);
+    assert_eq!(out, "#0000ff\n#ff0000\n#800080\n");
+
+    std::fs::write(&path, "0.5\n0.1 oops\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["-c", "red", "blue", "--sample-file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("(line 2): invalid number 'oops'"));
+}
+
+#[test]
+fn position_mismatch() {
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["-c", "red", "blue", "-P", "0", "0.5", "1", "-t", "3"])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr)
+        .contains("--position has 3 values but --custom has 2 colors"));
+}
+
+#[test]
+fn from_image() {
+    let path = std::env::temp_dir().join("gradient-from-image.png");
+    let img = path.to_str().unwrap();
+
+    // Left half white, right half red
+    gradient(&[
+        "-c", "white", "red", "--steps", "2", "-W", "20", "-H", "4", "--output", img,
+    ]);
+
+    let out = gradient(&["--from-image", img, "--colors", "2", "-t", "2"]);
+    assert_eq!(out, "#ff0000\n#ffffff\n");
+}
+
+#[test]
+fn list_with_colors() {
+    let out = gradient(&["--list-presets", "greys", "--with-colors"]);
+    assert!(out.starts_with("greys #ffffff #"));
+    assert!(out.ends_with(" #000000\n"));
+    assert_eq!(out.lines().count(), 1);
+}
+
+#[test]
+fn list_presets_search() {
+    let names = |search| {
+        gradient(&["--list-presets", search, "--with-colors"])
+            .lines()
+            .map(|s| s.split(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let bu = names("BU");
+    assert_eq!(bu, names("bu"));
+    assert_eq!(
+        bu,
+        ["bu-gn", "bu-pu", "gn-bu", "pu-bu", "pu-bu-gn", "rd-bu", "rd-yl-bu", "yl-gn-bu"]
+    );
+    assert_eq!(names("YL_GN"), ["rd-yl-gn", "yl-gn", "yl-gn-bu"]);
+
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--list-presets", "nope"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr),
+        "Error: No presets match 'nope'\n"
+    );
+}
+
+#[test]
+fn preset_catalog() {
+    let out = gradient(&["--list-presets", "--json", "--take", "3"]);
+    assert!(out.starts_with("[{\"name\":\""));
+    assert!(out.contains("{\"name\":\"greys\",\"colors\":[\"#ffffff\",\"#"));
+    assert!(out.ends_with("\"]}]\n"));
+    assert_eq!(out.lines().count(), 1);
+
+    let out = gradient(&["--list-presets", "viridis", "--json"]);
+    assert_eq!(out.matches('#').count(), 5);
+}
+
+#[test]
+fn plot_csv() {
+    let out = gradient(&["-c", "red", "blue", "--plot", "rgb", "--take", "3"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "t,R,G,B,alpha");
+    assert_eq!(lines[1], "0,1,0,0,1");
+    assert_eq!(lines[3], "1,0,0,1,1");
+}
+
+#[test]
+fn distance_matrix() {
+    let out = gradient(&["-c", "black", "white", "--take", "2", "--distance-matrix"]);
+    assert_eq!(
+        out,
+        ",#000000,#ffffff\n#000000,0.00,100.00\n#ffffff,100.00,0.00\n"
+    );
+
+    // Labels with commas are quoted
+    let args = [
+        "-c",
+        "black",
+        "white",
+        "-t",
+        "2",
+        "--distance-matrix",
+        "-o",
+        "rgb255",
+    ];
+    let out = gradient(&args);
+    assert_eq!(
+        out,
+        ",\"rgb(0,0,0)\",\"rgb(255,255,255)\"\n\"rgb(0,0,0)\",0.00,100.00\n\"rgb(255,255,255)\",100.00,0.00\n"
+    );
+    let out = gradient(&[&args[..6], &["-o", "rgb"]].concat());
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        ",\"rgb(0.00%,0.00%,0.00%)\",\"rgb(100.00%,100.00%,100.00%)\""
+    );
+    assert_eq!(lines[1], "\"rgb(0.00%,0.00%,0.00%)\",0.00,100.00");
+}
+
+#[test]
+fn quiet() {
+    let out = gradient(&["--file", "data/Neon_Green.ggr", "--take", "3", "--quiet"]);
+    assert_eq!(out.lines().count(), 3);
+    assert!(out.lines().all(|s| s.starts_with('#')));
+
+    let out = gradient(&["--file", "data/Neon_Green.ggr", "--info"]);
+    assert!(out.contains("Neon_Green.ggr"));
+    let out = gradient(&["--file", "data/Neon_Green.ggr", "--info", "--quiet"]);
+    assert!(!out.contains("Neon_Green.ggr"));
+}
+
+#[test]
+fn verbose() {
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args([
+            "-c",
+            "red",
+            "blue",
+            "gold",
+            "-m",
+            "rgb",
+            "-t",
+            "2",
+            "--verbose",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "#ff0000\n#ffd700\n");
+    let err = String::from_utf8_lossy(&out.stderr);
+    assert!(err.contains("domain: 0 .. 1\n"));
+    assert!(err.contains("blend mode: rgb\n"));
+    assert!(err.contains("interpolation: catmull-rom\n"));
+    assert!(err.contains("colors: 3\n"));
+
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["--file", "data/Neon_Green.ggr", "-t", "2", "-v"])
+        .output()
+        .unwrap();
+    let err = String::from_utf8_lossy(&out.stderr);
+    assert!(err.starts_with("Neon_Green.ggr\n"));
+    assert!(err.contains("segments: 4\n"));
+}
+
+#[test]
+fn centered_take() {
+    let args = ["-c", "red", "blue", "-m", "rgb", "-i", "linear"];
+    let out = gradient(&[&args[..], &["-t", "1", "--edges", "centered"]].concat());
+    assert_eq!(out, gradient(&[&args[..], &["-s", "0.5"]].concat()));
+    assert_eq!(out, "#800080\n");
+
+    let out = gradient(&[&args[..], &["-t", "1"]].concat());
+    assert_eq!(out, "#ff0000\n");
+}
+
+#[test]
+fn css_vars() {
+    let out = gradient(&["-c", "red", "blue", "-t", "2", "--css-vars", "brand"]);
+    assert_eq!(out, "--brand-0: #ff0000;\n--brand-1: #0000ff;\n");
+
+    let out = gradient(&["-c", "red", "-s", "0", "--css-vars", "--c", "-o", "rgb255"]);
+    assert_eq!(out, "--c-0: rgb(255,0,0);\n");
+}
+
+#[test]
+fn clicolor_env() {
+    let run = |vars: &[(&str, &str)]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+            .args(["-p", "rainbow", "-t", "3"])
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .envs(vars.iter().copied())
+            .output()
+            .unwrap();
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    assert!(!run(&[]).contains("\x1B["));
+    assert!(run(&[("CLICOLOR_FORCE", "1")]).contains("\x1B["));
+    assert!(!run(&[("CLICOLOR_FORCE", "0")]).contains("\x1B["));
+    assert!(!run(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]).contains("\x1B["));
+    assert!(run(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "")]).contains("\x1B["));
+    assert!(!run(&[("CLICOLOR", "0")]).contains("\x1B["));
+}
+
+#[test]
+fn generate() {
+    let args = ["--generate", "--seed", "42", "--colors", "4", "-t", "4"];
+    let out = gradient(&args);
+    assert_eq!(out.lines().count(), 4);
+    assert_eq!(out, gradient(&args));
+
+    let other = gradient(&["--generate", "--seed", "7", "-t", "4"]);
+    assert_ne!(out, other);
+}
+
+#[test]
+fn over_gradient() {
+    let args = [
+        "-c",
+        "transparent",
+        "-W",
+        "10",
+        "-H",
+        "1",
+        "--color-depth",
+        "24bit",
+    ];
+    let out = gradient(&[&args[..], &["--over-gradient", "red, blue"]].concat());
+    assert!(out.starts_with("\x1B[38;2;255;0;0;"));
+    assert!(out.contains(";48;2;0;0;255m"));
+
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args([&args[..], &["--over-gradient", "nope("]].concat())
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+}
+
+#[test]
+fn ansi_output() {
+    let args = ["-p", "rainbow", "-W", "20", "--ruler"];
+    let out = gradient(&args);
+    assert_eq!(out.lines().count(), 2);
+
+    let out = gradient(&[&args[..], &["--ansi-output"]].concat());
+    assert!(out.contains("\x1B["));
+    // Terminal only ruler is printed too
+    assert_eq!(out.lines().count(), 4);
+
+    // Colors output is unaffected
+    let out = gradient(&["-p", "rainbow", "-t", "2", "--ansi-output"]);
+    assert_eq!(out, "#6e40aa\n#6e40aa\n");
+}
+
+#[test]
+fn wide_bar_piped() {
+    // Not clamped to the terminal width when piped
+    let out = gradient(&["-p", "rainbow", "-W", "2000", "-H", "1"]);
+    assert_eq!(out.matches('\u{258C}').count(), 2000);
+
+    // Still capped at 10000
+    let out = gradient(&["-p", "rainbow", "-W", "20000", "-H", "1"]);
+    assert_eq!(out.matches('\u{258C}').count(), 10000);
+}
+
+#[test]
+fn max_colors() {
+    // Only limited on an interactive terminal, not when piped with forced colors
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["-p", "rainbow", "-t", "9", "--max-colors", "3", "--strict"])
+        .env_remove("NO_COLOR")
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).matches('#').count(), 9);
+
+    let out = gradient(&["-p", "rainbow", "-t", "9", "--max-colors", "3", "--strict"]);
+    assert_eq!(out.lines().count(), 9);
+}
+
+#[test]
+fn svg_spread_method() {
+    let path = std::env::temp_dir().join("gradient-test-spread.svg");
+    std::fs::write(
+        &path,
+        r##"<svg>
+        <linearGradient id="r" spreadMethod="reflect">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        <linearGradient id="p">
+            <stop offset="0" stop-color="red"/>
+            <stop offset="1" stop-color="blue"/>
+        </linearGradient>
+        </svg>"##,
+    )
+    .unwrap();
+    let path = path.to_str().unwrap();
+
+    let out = gradient(&["-f", path, "--svg-id", "r", "-s", "1.5,0.5,2"]);
+    assert_eq!(out, "#800080\n#800080\n#ff0000\n");
+
+    // Default pad clamps
+    let out = gradient(&["-f", path, "--svg-id", "p", "-s", "1.5"]);
+    assert_eq!(out, "#0000ff\n");
+}
+
+#[test]
+fn forced_color_watch() {
+    // --watch needs an interactive stdout, forced colors alone render once and exit
+    let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+        .args(["-f", "data/Neon_Green.ggr", "--watch"])
+        .env_remove("NO_COLOR")
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("\x1B["));
+}
+
+#[test]
+fn auto_background_piped() {
+    // No terminal query when piped, falls back to black
+    let out = gradient(&["-c", "transparent", "-t", "1", "-b", "auto"]);
+    assert_eq!(out, "#000000\n");
+}
+
+#[test]
+fn accessible_on() {
+    let out = gradient(&[
+        "-c",
+        "white",
+        "black",
+        "-t",
+        "3",
+        "--accessible-on",
+        "white",
+    ]);
+    assert_eq!(out, "#636363\n#000000\n");
+
+    // Nothing left is an error, not a panic
+    for args in [
+        &["-c", "white", "#eee", "-t", "3"][..],
+        &["-c", "white", "#eee", "-s", "0.2,0.8"][..],
+    ] {
+        let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+            .args([args, &["--accessible-on", "white"]].concat())
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8_lossy(&out.stderr),
+            "Error: no colors with contrast >= 4.5 against #ffffff\n"
+        );
+    }
+}
+
+#[test]
+fn repeat() {
+    let args = ["-c", "black", "white", "--repeat", "2"];
+    let out = gradient(&[&args[..], &["--sample", "0.25,0.75"]].concat());
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], lines[1]);
+
+    // --take hits the same positions, seams start the next cycle
+    let out = gradient(&[&args[..], &["--take", "5"]].concat());
+    let take: Vec<_> = out.lines().collect();
+    assert_eq!(take, ["#000000", lines[0], "#000000", lines[0], "#ffffff"]);
+    assert_ne!(lines[0], "#000000");
+}
+
+#[test]
+fn width_percent() {
+    // Percentage of 80 columns when there is no terminal, at least 10
+    let cells = |p| {
+        gradient(&["-p", "rainbow", "-H", "1", "--width-percent", p])
+            .matches('\u{258C}')
+            .count()
+    };
+    assert_eq!(cells("50"), 40);
+    assert_eq!(cells("100"), 80);
+    assert_eq!(cells("5"), 10);
+
+    for args in [
+        &["--width-percent", "50", "--width", "20"][..],
+        &["--width-percent", "0"][..],
+        &["--width-percent", "101"][..],
+    ] {
+        let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+            .args([&["-p", "rainbow"][..], args].concat())
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(2));
+    }
+}
+
+#[test]
+fn css_output() {
+    let args = ["-c", "red", "blue", "-t", "3", "--css-output"];
+    let out = gradient(&args);
+    assert_eq!(
+        out,
+        "linear-gradient(to right, #ff0000 0%, #8c53a2 50%, #0000ff 100%)\n"
+    );
+
+    let out = gradient(&[&args[..], &["--format", "rgb"]].concat());
+    assert_eq!(
+        out,
+        "linear-gradient(to right, rgb(100.00%,0.00%,0.00%) 0%, rgb(55.04%,32.56%,63.65%) 50%, rgb(0.00%,0.00%,100.00%) 100%)\n"
+    );
+
+    let out = gradient(&[&args[..], &["--angle", "45deg"]].concat());
+    assert!(out.starts_with("linear-gradient(45deg, #ff0000 0%"));
+
+    // 16 stops by default
+    let out = gradient(&["-p", "rainbow", "--css-output"]);
+    assert_eq!(out.matches('#').count(), 16);
+    assert!(out.contains(" 6.67%, "));
+}
+
+#[test]
+fn vertical() {
+    let args = [
+        "-c",
+        "red",
+        "blue",
+        "-m",
+        "rgb",
+        "--vertical",
+        "-W",
+        "10",
+        "--color-depth",
+        "24bit",
+    ];
+    let out = gradient(&[&args[..], &["-H", "4"]].concat());
+    let rows: Vec<_> = out.lines().collect();
+    assert_eq!(rows.len(), 4);
+
+    // Two colors per row, one in each half cell, same across the row
+    let cell = |row: &str| format!("{}\u{2580}", row.split('\u{2580}').next().unwrap());
+    for row in &rows {
+        assert_eq!(row.matches(&cell(row)).count(), 10);
+    }
+    assert_eq!(cell(rows[0]), "\x1B[38;2;255;0;0;48;2;219;0;36m\u{2580}");
+    assert_eq!(cell(rows[1]), "\x1B[38;2;182;0;73;48;2;146;0;109m\u{2580}");
+    assert_eq!(cell(rows[3]), "\x1B[38;2;36;0;219;48;2;0;0;255m\u{2580}");
+
+    // Taller by default
+    let out = gradient(&args);
+    assert_eq!(out.lines().count(), 16);
+}
+
+#[test]
+fn bar_char() {
+    let bar = |args: &[&str]| gradient(&[&["-W", "10", "-H", "1"][..], args].concat());
+
+    // Half blocks by default
+    let out = bar(&["-p", "rainbow"]);
+    assert_eq!(out.matches('\u{258C}').count(), 10);
+    assert_eq!(out, bar(&["-p", "rainbow", "--bar-char", "half"]));
+
+    let out = bar(&["-p", "rainbow", "--bar-char", "full"]);
+    assert_eq!(out.matches('\u{2588}').count(), 10);
+    assert!(!out.contains('\u{258C}'));
+    assert!(!out.contains(";48;"));
+
+    // Glyph follows luminance
+    let out = bar(&["-c", "white", "--bar-char", "ascii"]);
+    assert_eq!(out.matches("m#").count(), 10);
+    let out = bar(&["-c", "black", "--bar-char", "ascii"]);
+    assert_eq!(out.matches("m ").count(), 10);
+    let out = bar(&["-c", "white", "--bar-char", "shade"]);
+    assert_eq!(out.matches('\u{2593}').count(), 10);
+    let out = bar(&["-c", "black", "--bar-char", "shade"]);
+    assert_eq!(out.matches('\u{2591}').count(), 10);
+}
+
+#[test]
+fn gamma() {
+    let out = gradient(&["-c", "#808080", "-t", "1", "--gamma", "2.2"]);
+    assert_eq!(out, "#bababa\n");
+
+    for g in ["0", "-1", "nan", "inf", "x"] {
+        let out = Command::new(env!("CARGO_BIN_EXE_gradient"))
+            .args(["-c", "red", "-t", "1", &format!("--gamma={g}")])
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("expected a number greater than 0"));
+    }
+}